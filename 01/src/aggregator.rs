@@ -0,0 +1,108 @@
+use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::dialect::Dialect;
+use crate::dialect::Line;
+use crate::error::Error;
+use crate::ElfTotal;
+
+/// Orders elves from worst to best: fewer calories first, and on a tie the later elf first
+#[derive(Debug, PartialEq, Eq)]
+struct Ranked(ElfTotal);
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .total
+            .cmp(&other.0.total)
+            .then(other.0.index.cmp(&self.0.index))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Tracks the `n` elves carrying the most calories while input is fed in a line at a time. Only the running total of the current elf and the best `n` elves are kept, so memory
+/// does not grow with the length of the input.
+pub struct CalorieAggregator {
+    n: usize,
+    dialect: Dialect,
+    line_no: usize,
+    index: usize,
+    total: u64,
+    in_elf: bool,
+    // Min-heap, so the weakest of the best `n` is the one at the top
+    best: BinaryHeap<Reverse<Ranked>>,
+}
+
+impl CalorieAggregator {
+    pub fn new(n: usize, dialect: Dialect) -> Self {
+        CalorieAggregator {
+            n,
+            dialect,
+            line_no: 0,
+            index: 0,
+            total: 0,
+            in_elf: false,
+            best: BinaryHeap::new(),
+        }
+    }
+
+    fn end_elf(&mut self) {
+        let elf = Ranked(ElfTotal {
+            index: self.index,
+            total: self.total,
+        });
+        if self.best.len() < self.n {
+            self.best.push(Reverse(elf));
+        } else if let Some(mut weakest) = self.best.peek_mut() {
+            if elf > weakest.0 {
+                *weakest = Reverse(elf);
+            }
+        }
+        self.index += 1;
+        self.total = 0;
+        self.in_elf = false;
+    }
+
+    /// Feeds one line of input, without its line ending
+    pub fn push_line(&mut self, line: &str) -> Result<(), Error> {
+        self.line_no += 1;
+        match self.dialect.classify(line, self.line_no)? {
+            Line::Separator => {
+                if self.in_elf || self.dialect == Dialect::Strict {
+                    self.end_elf();
+                }
+            }
+            Line::Calories(num) => {
+                self.total = match self.total.checked_add(num) {
+                    Some(x) => x,
+                    None => return Err(Error::Overflow { elf: self.index }),
+                };
+                self.in_elf = true;
+            }
+            Line::Comment => {}
+        }
+        Ok(())
+    }
+
+    /// The best elves so far, largest first. An elf whose items are still being read is not
+    /// included until its separator or `finish` is seen.
+    pub fn top(&self) -> Vec<ElfTotal> {
+        let mut ranked = self.best.iter().map(|r| r.0 .0).collect::<Vec<ElfTotal>>();
+        ranked.sort_by(|a, b| b.total.cmp(&a.total).then(a.index.cmp(&b.index)));
+        ranked
+    }
+
+    /// Ends the input, counting the last elf
+    pub fn finish(mut self) -> Result<Vec<ElfTotal>, Error> {
+        if self.in_elf {
+            self.end_elf();
+        }
+        Ok(self.top())
+    }
+}
@@ -0,0 +1,58 @@
+use crate::error::parse_calories;
+use crate::error::ParseError;
+
+/// How the calorie list is laid out.
+///
+/// `Strict` is the puzzle format: every empty line ends an elf, so repeated empty lines produce
+/// elves carrying nothing. `Lenient` accepts CRLF endings, surrounding whitespace and `#`
+/// comments, and treats any run of blank lines as a single separator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Line {
+    Calories(u64),
+    Separator,
+    Comment,
+}
+
+impl Dialect {
+    /// Classifies one line of input, which is on 1-based line `line_no`
+    pub fn classify(&self, line: &str, line_no: usize) -> Result<Line, ParseError> {
+        match self {
+            Dialect::Strict => {
+                if line.is_empty() {
+                    Ok(Line::Separator)
+                } else {
+                    Ok(Line::Calories(parse_calories(line, line_no)?))
+                }
+            }
+            Dialect::Lenient => {
+                let content = match line.find('#') {
+                    Some(i) => &line[..i],
+                    None => line,
+                };
+                let trimmed = content.trim();
+                if trimmed.is_empty() {
+                    if content.len() == line.len() {
+                        return Ok(Line::Separator);
+                    }
+                    return Ok(Line::Comment);
+                }
+                parse_calories(trimmed, line_no)
+                    .map(Line::Calories)
+                    .map_err(|mut e| {
+                        // Point at the column in the original line, not the trimmed text
+                        let leading = content.len() - content.trim_start().len();
+                        e.column += content[..leading].chars().count();
+                        e.text = line.to_string();
+                        e
+                    })
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use core::fmt::Formatter;
+use std::fmt::Display;
+use std::num::IntErrorKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    Overflow,
+    NonNumeric,
+    StrayWhitespace,
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            ParseErrorKind::Overflow => write!(f, "number too large"),
+            ParseErrorKind::NonNumeric => write!(f, "non-numeric character"),
+            ParseErrorKind::StrayWhitespace => write!(f, "stray whitespace"),
+        }
+    }
+}
+
+/// A calorie line that could not be parsed. `line` and `column` are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    fn span(&self) -> usize {
+        match self.kind {
+            ParseErrorKind::Overflow => self.text.chars().count(),
+            ParseErrorKind::NonNumeric | ParseErrorKind::StrayWhitespace => 1,
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        // Whitespace such as '\t' or '\r' would throw the caret out of line
+        let snippet = self
+            .text
+            .chars()
+            .map(|c| if c.is_whitespace() { ' ' } else { c })
+            .collect::<String>();
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(
+            f,
+            "{} on line {}, column {}",
+            self.kind, self.line, self.column
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, snippet)?;
+        write!(
+            f,
+            "{} | {}{}",
+            gutter,
+            " ".repeat(self.column - 1),
+            "^".repeat(self.span())
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Read(std::io::Error),
+    Parse(ParseError),
+    Overflow {
+        elf: usize,
+    },
+    /// The sum of the `elves` highest totals overflowed, though each total fits
+    SumOverflow {
+        elves: usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Error::Read(e) => write!(f, "Could not read line: {}", e),
+            Error::Parse(e) => write!(f, "Could not parse calories: {}", e),
+            Error::Overflow { elf } => write!(f, "Calorie total overflowed at elf {}", elf),
+            Error::SumOverflow { elves } => {
+                write!(f, "Sum of the top {} calorie totals overflowed", elves)
+            }
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+impl From<Error> for String {
+    fn from(e: Error) -> Self {
+        e.to_string()
+    }
+}
+
+/// Parses one line of the calorie list, which is on 1-based line `line` of the input
+pub fn parse_calories(text: &str, line: usize) -> Result<u64, ParseError> {
+    let e = match text.parse::<u64>() {
+        Ok(x) => return Ok(x),
+        Err(e) => e,
+    };
+    let error = |column, kind| ParseError {
+        line,
+        column,
+        text: text.to_string(),
+        kind,
+    };
+    if let Some(i) = text.chars().position(char::is_whitespace) {
+        return Err(error(i + 1, ParseErrorKind::StrayWhitespace));
+    }
+    if *e.kind() == IntErrorKind::PosOverflow {
+        return Err(error(1, ParseErrorKind::Overflow));
+    }
+    let column = text.chars().position(|c| !c.is_ascii_digit()).unwrap_or(0);
+    Err(error(column + 1, ParseErrorKind::NonNumeric))
+}
@@ -1,89 +1,171 @@
 use std::io::BufRead;
 use std::io::Read;
-use std::io::Seek;
 
-fn solve_part_one<R: Read>(f: R) -> Result<u32, String> {
-    let mut greatest = 0;
-    let mut current_total = 0;
-    for line in std::io::BufReader::new(f).lines() {
-        let line = match line {
-            Ok(x) => x,
-            Err(e) => return Err(format!("Could not read line: {}", e)),
-        };
-        if line.is_empty() {
-            if current_total > greatest {
-                greatest = current_total
-            }
-            current_total = 0;
-        } else {
-            let num = match line.parse::<u32>() {
-                Ok(x) => x,
-                Err(e) => return Err(format!("Could not parse \"{}\" as i32: {}", line, e)),
-            };
-            current_total += num;
-        }
+mod aggregator;
+mod dialect;
+mod error;
+mod report;
+
+use aggregator::CalorieAggregator;
+use dialect::Dialect;
+use dialect::Line;
+use error::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Elf {
+    index: usize,
+    items: Vec<u64>,
+}
+
+impl Elf {
+    fn total(&self) -> Result<u64, Error> {
+        self.items.iter().try_fold(0u64, |total, item| {
+            total
+                .checked_add(*item)
+                .ok_or(Error::Overflow { elf: self.index })
+        })
     }
-    Ok(greatest)
 }
 
-fn insert_ordered<T: PartialOrd + Copy + core::fmt::Debug>(list: &mut [T], item: T) {
-    // Could be improved with a binary search
-    for (index, value) in list.iter().enumerate() {
-        if item > *value {
-            for i in (index + 1..list.len()).rev() {
-                list[i] = list[i - 1];
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ElfTotal {
+    index: usize,
+    total: u64,
+}
+
+fn for_each_elf<R: Read, F: FnMut(Elf) -> Result<(), Error>>(
+    f: R,
+    dialect: Dialect,
+    mut visit: F,
+) -> Result<(), Error> {
+    let mut elf = Elf {
+        index: 0,
+        items: Vec::new(),
+    };
+    let mut in_elf = false;
+    for (line_no, line) in std::io::BufReader::new(f).lines().enumerate() {
+        let line = line.map_err(Error::Read)?;
+        match dialect.classify(&line, line_no + 1)? {
+            Line::Separator => {
+                if !in_elf && dialect == Dialect::Lenient {
+                    continue;
+                }
+                let next = Elf {
+                    index: elf.index + 1,
+                    items: Vec::new(),
+                };
+                visit(std::mem::replace(&mut elf, next))?;
+                in_elf = false;
+            }
+            Line::Calories(num) => {
+                elf.items.push(num);
+                in_elf = true;
             }
-            list[index] = item;
-            break;
+            Line::Comment => {}
         }
     }
+    if in_elf {
+        visit(elf)?;
+    }
+    Ok(())
+}
+
+fn parse_elves<R: Read>(f: R, dialect: Dialect) -> Result<Vec<Elf>, Error> {
+    let mut elves = Vec::new();
+    for_each_elf(f, dialect, |elf| {
+        elves.push(elf);
+        Ok(())
+    })?;
+    Ok(elves)
 }
 
-fn solve_part_two<R: Read>(f: R) -> Result<u32, String> {
-    let mut greatest = [0, 0, 0];
-    let mut current_total = 0;
+/// Returns the `n` elves carrying the most calories, largest first. Ties are ranked by elf index.
+fn top_n<R: Read>(f: R, n: usize, dialect: Dialect) -> Result<Vec<ElfTotal>, Error> {
+    let mut aggregator = CalorieAggregator::new(n, dialect);
     for line in std::io::BufReader::new(f).lines() {
-        let line = match line {
-            Ok(x) => x,
-            Err(e) => return Err(format!("Could not read line: {}", e)),
-        };
-        if line.is_empty() {
-            if current_total > greatest[2] {
-                insert_ordered(&mut greatest, current_total);
-            }
-            current_total = 0;
-        } else {
-            let num = match line.parse::<u32>() {
-                Ok(x) => x,
-                Err(e) => return Err(format!("Could not parse \"{}\" as i32: {}", line, e)),
-            };
-            current_total += num;
-        }
-    }
-    if current_total > greatest[2] {
-        insert_ordered(&mut greatest, current_total);
+        aggregator.push_line(&line.map_err(Error::Read)?)?;
     }
+    aggregator.finish()
+}
+
+fn sum_totals(elves: &[ElfTotal]) -> Result<u64, Error> {
+    elves.iter().try_fold(0u64, |sum, elf| {
+        sum.checked_add(elf.total)
+            .ok_or(Error::SumOverflow { elves: elves.len() })
+    })
+}
 
-    let greatest_total = greatest
-        .iter()
-        .fold(0, |accumulator, element| accumulator + element);
-    Ok(greatest_total)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Answer {
+    part_one: u64,
+    part_two: u64,
 }
 
-fn main() -> Result<(), String> {
-    let mut f = match std::fs::File::open("./input.txt") {
-        Ok(f) => f,
-        Err(e) => return Err(format!("Error opening input.txt: {}", e)),
+/// Solves both parts in a single pass, so `f` does not need to be seekable
+fn solve<R: Read>(f: R, dialect: Dialect) -> Result<Answer, Error> {
+    let top = top_n(f, 3, dialect)?;
+    Ok(Answer {
+        part_one: sum_totals(&top[..top.len().min(1)])?,
+        part_two: sum_totals(&top)?,
+    })
+}
+
+fn run() -> Result<(), String> {
+    let (flags, modes): (Vec<String>, Vec<String>) = std::env::args()
+        .skip(1)
+        .partition(|arg| arg.starts_with("--"));
+    let mut dialect = Dialect::Strict;
+    let mut from_stdin = false;
+    for flag in &flags {
+        match flag.as_str() {
+            "--lenient" => dialect = Dialect::Lenient,
+            "--stdin" => from_stdin = true,
+            _ => return Err(format!("Unknown flag: {}", flag)),
+        }
+    }
+
+    let f: Box<dyn Read> = if from_stdin {
+        Box::new(std::io::stdin().lock())
+    } else {
+        match std::fs::File::open("./input.txt") {
+            Ok(f) => Box::new(f),
+            Err(e) => return Err(format!("Error opening input.txt: {}", e)),
+        }
     };
 
-    println!("Part One: {}", solve_part_one(&f)?);
-    if let Err(e) = f.rewind() {
-        return Err(format!("Could not rewind file: {}", e));
+    match modes.first().map(String::as_str) {
+        None => {}
+        Some("report") => {
+            print!("{}", report::render(&parse_elves(f, dialect)?)?);
+            return Ok(());
+        }
+        Some("top") => {
+            let n = match modes.get(1).map(|n| n.parse::<usize>()) {
+                Some(Ok(x)) => x,
+                _ => return Err("Usage: top <n>".to_string()),
+            };
+            for (rank, elf) in top_n(f, n, dialect)?.iter().enumerate() {
+                println!("{:>4}. Elf {:<8} {}", rank + 1, elf.index, elf.total);
+            }
+            return Ok(());
+        }
+        Some(mode) => return Err(format!("Unknown mode: {}", mode)),
     }
-    println!("Part Two: {}", solve_part_two(&f)?);
+
+    let answer = solve(f, dialect)?;
+    println!("Part One: {}", answer.part_one);
+    println!("Part Two: {}", answer.part_two);
     Ok(())
 }
 
+fn main() {
+    // Printed with Display rather than returned so multi-line parse errors keep their layout
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
 #[test]
 fn test_input() -> Result<(), String> {
     let input = r#"1000
@@ -101,11 +183,256 @@ fn test_input() -> Result<(), String> {
 
 10000"#;
 
-    let ans = solve_part_one(input.as_bytes())?;
-    assert_eq!(ans, 24_000);
+    let ans = solve(input.as_bytes(), Dialect::Strict)?;
+    assert_eq!(ans.part_one, 24_000);
+    assert_eq!(ans.part_two, 45_000);
+
+    Ok(())
+}
+
+#[test]
+fn test_top_n() -> Result<(), String> {
+    let input = "300\n\n100\n200\n\n50\n\n300\n";
+
+    let ans = top_n(input.as_bytes(), 3, Dialect::Strict)?;
+    assert_eq!(
+        ans,
+        vec![
+            ElfTotal {
+                index: 0,
+                total: 300
+            },
+            ElfTotal {
+                index: 1,
+                total: 300
+            },
+            ElfTotal {
+                index: 3,
+                total: 300
+            },
+        ]
+    );
+
+    let ans = top_n(input.as_bytes(), 10, Dialect::Strict)?;
+    assert_eq!(ans.len(), 4);
+    assert_eq!(
+        ans[3],
+        ElfTotal {
+            index: 2,
+            total: 50
+        }
+    );
 
-    let ans = solve_part_two(input.as_bytes())?;
-    assert_eq!(ans, 45_000);
+    assert!(top_n(input.as_bytes(), 0, Dialect::Strict)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_report() -> Result<(), String> {
+    let input = "1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000";
+
+    let elves = parse_elves(input.as_bytes(), Dialect::Strict)?;
+    assert_eq!(elves.len(), 5);
+    assert_eq!(
+        elves[2],
+        Elf {
+            index: 2,
+            items: vec![5000, 6000]
+        }
+    );
+
+    let summary = report::summarize(&elves)?.ok_or("No summary")?;
+    assert_eq!(summary.count, 5);
+    assert_eq!(summary.min, 4000);
+    assert_eq!(summary.max, 24000);
+    assert_eq!(summary.mean, 11000.0);
+    assert_eq!(summary.median, 10000.0);
+    assert_eq!(summary.percentiles[0], (10, 4000));
+    assert_eq!(summary.percentiles[5], (99, 24000));
+
+    let bins = report::histogram(&elves)?;
+    assert_eq!(bins.len(), 10);
+    assert_eq!(bins[0], (4000, 6000, 2));
+    assert_eq!(bins.iter().map(|(_, _, count)| count).sum::<usize>(), 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_error() -> Result<(), String> {
+    let input = "1000\n2000\n\n30x0\n";
+    let e = match parse_elves(input.as_bytes(), Dialect::Strict) {
+        Err(Error::Parse(e)) => e,
+        other => return Err(format!("Expected parse error, got {:?}", other)),
+    };
+    assert_eq!(e.line, 4);
+    assert_eq!(e.column, 3);
+    assert_eq!(e.text, "30x0");
+    assert_eq!(e.kind, error::ParseErrorKind::NonNumeric);
+    assert_eq!(
+        e.to_string(),
+        "non-numeric character on line 4, column 3\n  |\n4 | 30x0\n  |   ^"
+    );
+
+    let e = error::parse_calories("12 ", 7).unwrap_err();
+    assert_eq!(
+        (e.column, e.kind),
+        (3, error::ParseErrorKind::StrayWhitespace)
+    );
+    let e = error::parse_calories("123\r", 7).unwrap_err();
+    assert_eq!(
+        (e.column, e.kind),
+        (4, error::ParseErrorKind::StrayWhitespace)
+    );
+    let e = error::parse_calories("99999999999999999999", 7).unwrap_err();
+    assert_eq!((e.column, e.kind), (1, error::ParseErrorKind::Overflow));
+    assert!(e.to_string().ends_with(&"^".repeat(20)));
+
+    Ok(())
+}
+
+#[test]
+fn test_overflow() -> Result<(), String> {
+    // Every elf carries more than u32::MAX calories
+    let elf = "2000000000\n".repeat(3);
+    let input = vec![elf; 1000].join("\n");
+    let ans = solve(input.as_bytes(), Dialect::Strict)?;
+    assert_eq!(ans.part_one, 6_000_000_000);
+    assert_eq!(ans.part_two, 18_000_000_000);
+
+    let input = "1\n\n18446744073709551615\n1\n";
+    match solve(input.as_bytes(), Dialect::Strict) {
+        Err(Error::Overflow { elf: 1 }) => {}
+        other => return Err(format!("Expected overflow on elf 1, got {:?}", other)),
+    }
+
+    // Each total fits, only their sum for part two doesn't
+    let input = "18446744073709551615\n\n1\n";
+    match solve(input.as_bytes(), Dialect::Strict) {
+        Err(Error::SumOverflow { elves: 2 }) => {}
+        other => return Err(format!("Expected overflow of the sum, got {:?}", other)),
+    }
+    assert_eq!(
+        String::from(Error::SumOverflow { elves: 3 }),
+        "Sum of the top 3 calorie totals overflowed"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_dialect() -> Result<(), String> {
+    let input = "# rations\r\n1000\r\n 2000 # snacks\r\n\r\n\r\n\r\n4000\r\n\r\n\r\n";
+
+    let elves = parse_elves(input.as_bytes(), Dialect::Lenient)?;
+    assert_eq!(
+        elves,
+        vec![
+            Elf {
+                index: 0,
+                items: vec![1000, 2000]
+            },
+            Elf {
+                index: 1,
+                items: vec![4000]
+            },
+        ]
+    );
+
+    let e = match parse_elves(input.as_bytes(), Dialect::Strict) {
+        Err(Error::Parse(e)) => e,
+        other => return Err(format!("Expected parse error, got {:?}", other)),
+    };
+    assert_eq!(
+        (e.line, e.kind),
+        (1, error::ParseErrorKind::StrayWhitespace)
+    );
+
+    let input = "1000\n\n\n2000\n";
+    assert_eq!(parse_elves(input.as_bytes(), Dialect::Strict)?.len(), 3);
+    assert_eq!(parse_elves(input.as_bytes(), Dialect::Lenient)?.len(), 2);
+
+    let e = match parse_elves("1000\n  20x0  # note\n".as_bytes(), Dialect::Lenient) {
+        Err(Error::Parse(e)) => e,
+        other => return Err(format!("Expected parse error, got {:?}", other)),
+    };
+    assert_eq!((e.line, e.column), (2, 5));
+    assert_eq!(e.text, "  20x0  # note");
+
+    Ok(())
+}
+
+#[test]
+fn test_aggregator() -> Result<(), String> {
+    let mut aggregator = CalorieAggregator::new(2, Dialect::Strict);
+    let push = |aggregator: &mut CalorieAggregator, lines: &[&str]| {
+        lines.iter().try_for_each(|line| aggregator.push_line(line))
+    };
+    push(&mut aggregator, &["100"])?;
+    assert!(aggregator.top().is_empty());
+    push(&mut aggregator, &["200", "", "500"])?;
+    assert_eq!(
+        aggregator.top(),
+        vec![ElfTotal {
+            index: 0,
+            total: 300
+        }]
+    );
+    push(&mut aggregator, &["", "300", "", "10"])?;
+    assert_eq!(
+        aggregator.top(),
+        vec![
+            ElfTotal {
+                index: 1,
+                total: 500
+            },
+            ElfTotal {
+                index: 0,
+                total: 300
+            },
+        ]
+    );
+    push(&mut aggregator, &["", "900"])?;
+    assert_eq!(
+        aggregator.finish()?,
+        vec![
+            ElfTotal {
+                index: 4,
+                total: 900
+            },
+            ElfTotal {
+                index: 1,
+                total: 500
+            },
+        ]
+    );
+
+    let input = (0..10_000)
+        .map(|i| (i % 97).to_string())
+        .collect::<Vec<String>>()
+        .join("\n\n");
+    let mut aggregator = CalorieAggregator::new(3, Dialect::Strict);
+    for line in input.lines() {
+        aggregator.push_line(line)?;
+    }
+    assert_eq!(
+        aggregator.finish()?,
+        vec![
+            ElfTotal {
+                index: 96,
+                total: 96
+            },
+            ElfTotal {
+                index: 193,
+                total: 96
+            },
+            ElfTotal {
+                index: 290,
+                total: 96
+            },
+        ]
+    );
 
     Ok(())
 }
@@ -0,0 +1,116 @@
+use crate::error::Error;
+use crate::Elf;
+
+const PERCENTILES: [u32; 6] = [10, 25, 50, 75, 90, 99];
+const HISTOGRAM_BINS: u64 = 10;
+const HISTOGRAM_WIDTH: usize = 50;
+
+#[derive(Debug, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub median: f64,
+    pub percentiles: Vec<(u32, u64)>,
+}
+
+/// Nearest-rank percentile of an ascending list
+fn percentile(sorted: &[u64], p: u32) -> u64 {
+    let rank = (p as usize * sorted.len()).div_ceil(100);
+    sorted[rank.max(1) - 1]
+}
+
+fn sorted_totals(elves: &[Elf]) -> Result<Vec<u64>, Error> {
+    let mut totals = elves
+        .iter()
+        .map(Elf::total)
+        .collect::<Result<Vec<u64>, Error>>()?;
+    totals.sort_unstable();
+    Ok(totals)
+}
+
+pub fn summarize(elves: &[Elf]) -> Result<Option<Summary>, Error> {
+    let totals = sorted_totals(elves)?;
+    let count = totals.len();
+    let (min, max) = match (totals.first(), totals.last()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return Ok(None),
+    };
+    let mean = totals.iter().map(|x| *x as f64).sum::<f64>() / count as f64;
+    let median = if count.is_multiple_of(2) {
+        (totals[count / 2 - 1] as f64 + totals[count / 2] as f64) / 2.0
+    } else {
+        totals[count / 2] as f64
+    };
+    let percentiles = PERCENTILES
+        .iter()
+        .map(|p| (*p, percentile(&totals, *p)))
+        .collect();
+    Ok(Some(Summary {
+        count,
+        min,
+        max,
+        mean,
+        median,
+        percentiles,
+    }))
+}
+
+/// Splits the totals into equal-width bins between the min and max total.
+/// Each bin is returned as (lower bound, upper bound, number of elves), bounds inclusive.
+pub fn histogram(elves: &[Elf]) -> Result<Vec<(u64, u64, usize)>, Error> {
+    let totals = sorted_totals(elves)?;
+    let (min, max) = match (totals.first(), totals.last()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return Ok(Vec::new()),
+    };
+    let width = ((max - min) / HISTOGRAM_BINS + 1).max(1);
+    let mut bins = Vec::new();
+    let mut lower = min;
+    while lower <= max {
+        let upper = lower.saturating_add(width - 1).min(max);
+        let count = totals
+            .iter()
+            .filter(|x| lower <= **x && **x <= upper)
+            .count();
+        bins.push((lower, upper, count));
+        if upper == max {
+            break;
+        }
+        lower = upper + 1;
+    }
+    Ok(bins)
+}
+
+pub fn render(elves: &[Elf]) -> Result<String, Error> {
+    let summary = match summarize(elves)? {
+        Some(x) => x,
+        None => return Ok("No elves in input\n".to_string()),
+    };
+    let mut out = String::new();
+    out += &format!("Count:  {}\n", summary.count);
+    out += &format!("Min:    {}\n", summary.min);
+    out += &format!("Max:    {}\n", summary.max);
+    out += &format!("Mean:   {:.2}\n", summary.mean);
+    out += &format!("Median: {:.1}\n", summary.median);
+    for (p, value) in &summary.percentiles {
+        out += &format!("P{:<2}:    {}\n", p, value);
+    }
+
+    let bins = histogram(elves)?;
+    let largest = bins.iter().map(|(_, _, count)| *count).max().unwrap_or(0);
+    out += "\nHistogram:\n";
+    for (lower, upper, count) in bins {
+        let bar = count * HISTOGRAM_WIDTH / largest.max(1);
+        out += &format!(
+            "{:>8} - {:<8} | {:<width$} {}\n",
+            lower,
+            upper,
+            "#".repeat(bar),
+            count,
+            width = HISTOGRAM_WIDTH
+        );
+    }
+    Ok(out)
+}
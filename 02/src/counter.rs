@@ -0,0 +1,198 @@
+use std::str::FromStr;
+
+use crate::round_score;
+use crate::Outcome;
+use crate::Shape;
+
+const OUTCOMES: [Outcome; 3] = [Outcome::Win, Outcome::Draw, Outcome::Lose];
+
+/// Upper limit on states times rounds, since a one-byte back-pointer is kept for each
+const MAX_BACK_POINTERS: usize = 1 << 28;
+
+/// Set in a back-pointer when the outcome's count was already at its cap and stayed there
+const CLAMPED: u8 = 1 << 2;
+
+fn slot(outcome: Outcome) -> usize {
+    match outcome {
+        Outcome::Win => 0,
+        Outcome::Draw => 1,
+        Outcome::Lose => 2,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Bounds {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Bounds {
+    /// Highest count worth telling apart. Past an upper bound the plan is invalid, and with only
+    /// a lower bound every count from `min` up is as good as any other.
+    fn cap(&self) -> usize {
+        self.max.unwrap_or(self.min)
+    }
+}
+
+/// Limits on how many rounds may end in each outcome, indexed by `slot`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Constraints([Bounds; 3]);
+
+impl Constraints {
+    /// Adds a constraint written as `wins`, `draws` or `losses` followed by `=`, `<=` or `>=` and
+    /// a count, e.g. `draws<=5`
+    pub fn add(&mut self, s: &str) -> Result<(), String> {
+        let (name, op, count) = match s.find(['=', '<', '>']) {
+            Some(i) if s[i..].starts_with("<=") || s[i..].starts_with(">=") => {
+                (&s[..i], &s[i..i + 2], &s[i + 2..])
+            }
+            Some(i) if s[i..].starts_with('=') => (&s[..i], &s[i..i + 1], &s[i + 1..]),
+            _ => return Err(format!("Constraint has no =, <= or >=: {}", s)),
+        };
+        let outcome = match name {
+            "wins" => Outcome::Win,
+            "draws" => Outcome::Draw,
+            "losses" => Outcome::Lose,
+            _ => return Err(format!("Not wins, draws, or losses: {}", name)),
+        };
+        let count = match count.parse::<usize>() {
+            Ok(x) => x,
+            Err(e) => return Err(format!("Could not parse count: {}, {}", count, e)),
+        };
+        let bounds = &mut self.0[slot(outcome)];
+        match op {
+            "=" => {
+                bounds.min = bounds.min.max(count);
+                bounds.max = Some(bounds.max.map_or(count, |m| m.min(count)));
+            }
+            "<=" => bounds.max = Some(bounds.max.map_or(count, |m| m.min(count))),
+            _ => bounds.min = bounds.min.max(count),
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Constraints {
+    type Err = String;
+
+    /// Parses whitespace-separated constraints, see `Constraints::add`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut constraints = Constraints::default();
+        for constraint in s.split_whitespace() {
+            constraints.add(constraint)?;
+        }
+        Ok(constraints)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub score: u32,
+    pub moves: Vec<Shape>,
+}
+
+/// Finds the highest scoring responses to `opponents` that satisfy `constraints`.
+///
+/// Dynamic programming over the rounds, where the state is how many rounds have ended in each
+/// constrained outcome so far. Ties between plans go to the one preferring wins, then draws,
+/// in the earliest round.
+pub fn best_response(opponents: &[Shape], constraints: &Constraints) -> Result<Plan, String> {
+    // No count can exceed the number of rounds, so neither need any bound
+    let rounds = opponents.len();
+    if constraints.0.iter().any(|b| b.min > rounds) {
+        return Err("No guide satisfies the constraints".to_string());
+    }
+    let bounds = constraints.0.map(|b| Bounds {
+        min: b.min,
+        max: b.max.map(|m| m.min(rounds)),
+    });
+    let dims = bounds.map(|b| b.cap() + 1);
+    let num_states = dims
+        .iter()
+        .try_fold(1usize, |acc, d| acc.checked_mul(*d))
+        .filter(|n| n.saturating_mul(opponents.len()) <= MAX_BACK_POINTERS)
+        .ok_or("Constraints allow too many combinations of outcomes")?;
+    let encode = |counts: [usize; 3]| counts[0] + dims[0] * (counts[1] + dims[1] * counts[2]);
+    let decode = |state: usize| {
+        [
+            state % dims[0],
+            state / dims[0] % dims[1],
+            state / (dims[0] * dims[1]),
+        ]
+    };
+
+    let mut best: Vec<Option<u32>> = vec![None; num_states];
+    best[0] = Some(0);
+    // For each round and state reached, the slot of the outcome chosen, with `CLAMPED` set when
+    // the state before it had the same counts
+    let mut parents: Vec<Vec<u8>> = Vec::with_capacity(opponents.len());
+    for opponent in opponents {
+        let mut next: Vec<Option<u32>> = vec![None; num_states];
+        let mut round_parents = vec![0u8; num_states];
+        for (state, score) in best.iter().enumerate() {
+            let score = match score {
+                Some(x) => *x,
+                None => continue,
+            };
+            let counts = decode(state);
+            for outcome in OUTCOMES {
+                let i = slot(outcome);
+                let mut new_counts = counts;
+                let mut parent = i as u8;
+                new_counts[i] += 1;
+                if new_counts[i] > bounds[i].cap() {
+                    if bounds[i].max.is_some() {
+                        continue;
+                    }
+                    new_counts[i] = bounds[i].cap();
+                    parent |= CLAMPED;
+                }
+                let new_state = encode(new_counts);
+                let me = outcome.response_to(*opponent);
+                let candidate = score + round_score(*opponent, me);
+                if next[new_state].is_none_or(|x| candidate > x) {
+                    next[new_state] = Some(candidate);
+                    round_parents[new_state] = parent;
+                }
+            }
+        }
+        best = next;
+        parents.push(round_parents);
+    }
+
+    let end = (0..num_states)
+        .filter(|state| {
+            let counts = decode(*state);
+            (0..3).all(|i| counts[i] >= bounds[i].min)
+        })
+        .filter_map(|state| best[state].map(|score| (score, state)))
+        .max_by_key(|(score, state)| (*score, std::cmp::Reverse(*state)));
+    let (score, mut state) = match end {
+        Some(x) => x,
+        None => return Err("No guide satisfies the constraints".to_string()),
+    };
+
+    let mut moves = vec![Shape::Rock; opponents.len()];
+    for (round, opponent) in opponents.iter().enumerate().rev() {
+        let parent = parents[round][state];
+        let i = (parent & 3) as usize;
+        moves[round] = OUTCOMES[i].response_to(*opponent);
+        let mut counts = decode(state);
+        if parent & CLAMPED == 0 {
+            counts[i] -= 1;
+        }
+        state = encode(counts);
+    }
+    Ok(Plan { score, moves })
+}
+
+/// Writes a guide in the `A Y` format, with the second column as the shape to play
+pub fn to_guide(opponents: &[Shape], moves: &[Shape]) -> String {
+    let mut out = String::new();
+    for (opponent, me) in opponents.iter().zip(moves) {
+        let opponent = ["A", "B", "C"][opponent.index()];
+        let me = ["X", "Y", "Z"][me.index()];
+        out += &format!("{} {}\n", opponent, me);
+    }
+    out
+}
@@ -0,0 +1,165 @@
+use crate::issues_error;
+use crate::GuideIssue;
+use crate::GuideLine;
+use crate::Outcome;
+
+/// Shapes of Rock Paper Scissors as `name opponent-letter my-letter points`
+pub const ROCK_PAPER_SCISSORS: &str = "Rock A X 1, Paper B Y 2, Scissors C Z 3";
+
+/// Rock Paper Scissors Lizard Spock. Opponent letters run A to E and ours V to Z, both in
+/// order of points.
+pub const ROCK_PAPER_SCISSORS_LIZARD_SPOCK: &str =
+    "Rock A V 1, Spock E Z 5, Paper B W 2, Lizard D Y 4, Scissors C X 3";
+
+/// The outcome of shape `me` against `opponent`, both indexes into a cycle of `n` shapes where
+/// each shape beats the `(n - 1) / 2` shapes before it, wrapping around
+pub fn cyclic_outcome(me: usize, opponent: usize, n: usize) -> Outcome {
+    let distance = (me + n - opponent) % n;
+    if distance == 0 {
+        Outcome::Draw
+    } else if distance <= (n - 1) / 2 {
+        Outcome::Win
+    } else {
+        Outcome::Lose
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeDef {
+    pub name: String,
+    pub opponent_letter: String,
+    pub my_letter: String,
+    pub points: u32,
+}
+
+/// A game with any odd number of shapes under cyclic dominance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    shapes: Vec<ShapeDef>,
+}
+
+impl Game {
+    /// Builds a game from comma-separated `name opponent-letter my-letter points` entries. Shapes
+    /// are listed in cycle order, so each beats the `(n - 1) / 2` shapes listed before it,
+    /// wrapping around.
+    pub fn from_spec(spec: &str) -> Result<Game, String> {
+        let mut shapes = Vec::new();
+        for entry in spec.split(',') {
+            let parts = entry.split_whitespace().collect::<Vec<&str>>();
+            if parts.len() != 4 {
+                return Err(format!(
+                    "Shape does not contain 4 elements: {}",
+                    entry.trim()
+                ));
+            }
+            let points = match parts[3].parse::<u32>() {
+                Ok(x) => x,
+                Err(e) => return Err(format!("Could not parse points: {}, {}", parts[3], e)),
+            };
+            shapes.push(ShapeDef {
+                name: parts[0].to_string(),
+                opponent_letter: parts[1].to_string(),
+                my_letter: parts[2].to_string(),
+                points,
+            });
+        }
+        if shapes.len() < 3 || shapes.len() % 2 == 0 {
+            return Err(format!(
+                "A game needs an odd number of at least 3 shapes, found {}",
+                shapes.len()
+            ));
+        }
+        for (i, shape) in shapes.iter().enumerate() {
+            for other in &shapes[i + 1..] {
+                if shape.name == other.name
+                    || shape.opponent_letter == other.opponent_letter
+                    || shape.my_letter == other.my_letter
+                {
+                    return Err(format!(
+                        "Shapes {} and {} share a name or letter",
+                        shape.name, other.name
+                    ));
+                }
+            }
+        }
+        Ok(Game { shapes })
+    }
+
+    pub fn shapes(&self) -> &[ShapeDef] {
+        &self.shapes
+    }
+
+    pub fn outcome(&self, me: usize, opponent: usize) -> Outcome {
+        cyclic_outcome(me, opponent, self.shapes.len())
+    }
+
+    pub fn round_score(&self, opponent: usize, me: usize) -> u32 {
+        self.shapes[me].points + self.outcome(me, opponent).score()
+    }
+
+    pub fn opponent_shape(&self, letter: &str) -> Result<usize, String> {
+        match self.shapes.iter().position(|s| s.opponent_letter == letter) {
+            Some(x) => Ok(x),
+            None => Err(format!("Not an opponent shape: {}", letter)),
+        }
+    }
+
+    pub fn my_shape(&self, letter: &str) -> Result<usize, String> {
+        match self.shapes.iter().position(|s| s.my_letter == letter) {
+            Some(x) => Ok(x),
+            None => Err(format!("Not one of our shapes: {}", letter)),
+        }
+    }
+
+    /// The shape giving `outcome` against `opponent`. With more than three shapes several may
+    /// qualify, in which case the one worth the most points is played.
+    pub fn response(&self, opponent: usize, outcome: Outcome) -> usize {
+        (0..self.shapes.len())
+            .filter(|me| self.outcome(*me, opponent) == outcome)
+            .max_by_key(|me| (self.shapes[*me].points, std::cmp::Reverse(*me)))
+            .unwrap_or(opponent)
+    }
+
+    /// Scores every line, collecting every letter that isn't part of the game. `parse` reads the
+    /// second column and `respond` turns it into the shape we play against the opponent's.
+    fn score_guide<C, P, F>(&self, lines: &[GuideLine], parse: P, respond: F) -> Result<u32, String>
+    where
+        P: Fn(&str) -> Result<C, String>,
+        F: Fn(usize, C) -> usize,
+    {
+        let mut score = 0;
+        let mut issues = Vec::new();
+        for line in lines {
+            match (self.opponent_shape(&line.opponent), parse(&line.column)) {
+                (Ok(opponent), Ok(column)) => {
+                    score += self.round_score(opponent, respond(opponent, column))
+                }
+                (opponent, column) => {
+                    let reasons = [opponent.err(), column.err()].into_iter().flatten();
+                    issues.extend(reasons.map(|reason| GuideIssue {
+                        line: line.line,
+                        reason,
+                    }));
+                }
+            }
+        }
+        if !issues.is_empty() {
+            return Err(issues_error(&issues));
+        }
+        Ok(score)
+    }
+
+    /// Scores a guide whose second column is the shape we play
+    pub fn score_shapes(&self, lines: &[GuideLine]) -> Result<u32, String> {
+        self.score_guide(lines, |letter| self.my_shape(letter), |_, me| me)
+    }
+
+    /// Scores a guide whose second column is the outcome, read as in the puzzle
+    pub fn score_outcomes(&self, lines: &[GuideLine]) -> Result<u32, String> {
+        self.score_guide(
+            lines,
+            |letter| letter.parse::<Outcome>(),
+            |opponent, outcome| self.response(opponent, outcome),
+        )
+    }
+}
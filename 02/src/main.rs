@@ -1,121 +1,476 @@
+use core::fmt::Formatter;
+use std::fmt::Display;
 use std::io::BufRead;
 use std::io::Read;
 use std::io::Seek;
+use std::str::FromStr;
 
-fn solve_part_one<R: Read>(f: R) -> Result<u32, String> {
-    let mut score = 0;
-    for line in std::io::BufReader::new(f).lines() {
+mod counter;
+mod game;
+mod simulate;
+mod transcript;
+
+use game::Game;
+use transcript::Transcript;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Rock,
+    Paper,
+    Scissors,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+impl Shape {
+    const ALL: [Shape; 3] = [Shape::Rock, Shape::Paper, Shape::Scissors];
+
+    fn beats(&self) -> Shape {
+        match self {
+            Shape::Rock => Shape::Scissors,
+            Shape::Paper => Shape::Rock,
+            Shape::Scissors => Shape::Paper,
+        }
+    }
+
+    fn beaten_by(&self) -> Shape {
+        match self {
+            Shape::Rock => Shape::Paper,
+            Shape::Paper => Shape::Scissors,
+            Shape::Scissors => Shape::Rock,
+        }
+    }
+
+    fn score(&self) -> u32 {
+        match self {
+            Shape::Rock => 1,
+            Shape::Paper => 2,
+            Shape::Scissors => 3,
+        }
+    }
+
+    /// Position in the dominance cycle, where each shape beats the one before it
+    fn index(&self) -> usize {
+        match self {
+            Shape::Rock => 0,
+            Shape::Paper => 1,
+            Shape::Scissors => 2,
+        }
+    }
+
+    /// The outcome for this shape when played against `opponent`
+    fn against(&self, opponent: Shape) -> Outcome {
+        game::cyclic_outcome(self.index(), opponent.index(), 3)
+    }
+}
+
+impl FromStr for Shape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "A" => Ok(Shape::Rock),
+            "B" => Ok(Shape::Paper),
+            "C" => Ok(Shape::Scissors),
+            _ => Err(format!("Not A, B, or C: {}", s)),
+        }
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "X" => Ok(Outcome::Lose),
+            "Y" => Ok(Outcome::Draw),
+            "Z" => Ok(Outcome::Win),
+            _ => Err(format!("Not X, Y, or Z: {}", s)),
+        }
+    }
+}
+
+impl Outcome {
+    /// Position of the column that names this outcome in the puzzle, X, Y or Z
+    fn index(&self) -> usize {
+        match self {
+            Outcome::Lose => 0,
+            Outcome::Draw => 1,
+            Outcome::Win => 2,
+        }
+    }
+
+    fn score(&self) -> u32 {
+        match self {
+            Outcome::Lose => 0,
+            Outcome::Draw => 3,
+            Outcome::Win => 6,
+        }
+    }
+
+    /// The shape that produces this outcome against `opponent`
+    fn response_to(&self, opponent: Shape) -> Shape {
+        match self {
+            Outcome::Lose => opponent.beats(),
+            Outcome::Draw => opponent,
+            Outcome::Win => opponent.beaten_by(),
+        }
+    }
+}
+
+/// Score of a single round from our point of view
+fn round_score(opponent: Shape, me: Shape) -> u32 {
+    me.score() + me.against(opponent).score()
+}
+
+/// How the second column of the strategy guide decides what we play
+trait GuideInterpretation {
+    fn response(&self, opponent: Shape, column: &str) -> Result<Shape, String>;
+    fn describe(&self) -> String;
+}
+
+fn column_index(column: &str) -> Result<usize, String> {
+    match column {
+        "X" => Ok(0),
+        "Y" => Ok(1),
+        "Z" => Ok(2),
+        _ => Err(format!("Not X, Y, or Z: {}", column)),
+    }
+}
+
+/// X, Y and Z name the shape to play
+struct ShapeMapping([Shape; 3]);
+
+/// X, Y and Z name the outcome the round should have. The outcomes are indexed by the one each
+/// column names in the puzzle, so `PUZZLE` reads every column as it parses.
+struct OutcomeMapping([Outcome; 3]);
+
+impl ShapeMapping {
+    const PUZZLE: ShapeMapping = ShapeMapping([Shape::Rock, Shape::Paper, Shape::Scissors]);
+}
+
+impl OutcomeMapping {
+    const PUZZLE: OutcomeMapping = OutcomeMapping([Outcome::Lose, Outcome::Draw, Outcome::Win]);
+}
+
+impl GuideInterpretation for ShapeMapping {
+    fn response(&self, _opponent: Shape, column: &str) -> Result<Shape, String> {
+        Ok(self.0[column_index(column)?])
+    }
+
+    fn describe(&self) -> String {
+        format!("X={:?} Y={:?} Z={:?}", self.0[0], self.0[1], self.0[2])
+    }
+}
+
+impl GuideInterpretation for OutcomeMapping {
+    fn response(&self, opponent: Shape, column: &str) -> Result<Shape, String> {
+        Ok(self.0[column.parse::<Outcome>()?.index()].response_to(opponent))
+    }
+
+    fn describe(&self) -> String {
+        format!("X={:?} Y={:?} Z={:?}", self.0[0], self.0[1], self.0[2])
+    }
+}
+
+fn permutations<T: Copy>([a, b, c]: [T; 3]) -> [[T; 3]; 6] {
+    [
+        [a, b, c],
+        [a, c, b],
+        [b, a, c],
+        [b, c, a],
+        [c, a, b],
+        [c, b, a],
+    ]
+}
+
+/// Every way of reading X, Y and Z as distinct shapes or distinct outcomes
+fn all_interpretations() -> Vec<Box<dyn GuideInterpretation>> {
+    let mut interpretations: Vec<Box<dyn GuideInterpretation>> = Vec::new();
+    for shapes in permutations(ShapeMapping::PUZZLE.0) {
+        interpretations.push(Box::new(ShapeMapping(shapes)));
+    }
+    for outcomes in permutations(OutcomeMapping::PUZZLE.0) {
+        interpretations.push(Box::new(OutcomeMapping(outcomes)));
+    }
+    interpretations
+}
+
+/// Rounds of a strategy guide as (opponent's shape, second column) pairs
+type Guide = Vec<(Shape, String)>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GuideIssue {
+    line: usize,
+    reason: String,
+}
+
+impl Display for GuideIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "Line {}: {}", self.line, self.reason)
+    }
+}
+
+/// A well-formed line of a strategy guide, with its two columns not yet interpreted
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GuideLine {
+    line: usize,
+    opponent: String,
+    column: String,
+}
+
+/// Splits the guide into its two columns, collecting every malformed line instead of stopping at
+/// the first. Blank lines at the end and CRLF line endings are accepted.
+fn read_guide_lines<R: Read>(f: R) -> Result<(Vec<GuideLine>, Vec<GuideIssue>), String> {
+    let mut lines = Vec::new();
+    let mut issues = Vec::new();
+    let mut blank_lines = Vec::new();
+    for (i, line) in std::io::BufReader::new(f).lines().enumerate() {
         let line = match line {
             Ok(x) => x,
             Err(e) => return Err(format!("Could not read line: {}", e)),
         };
         let split: Vec<&str> = line.split_whitespace().collect();
+        if split.is_empty() {
+            blank_lines.push(i + 1);
+            continue;
+        }
+        // Only trailing blank lines are allowed, and this one isn't trailing
+        for blank in blank_lines.drain(..) {
+            issues.push(GuideIssue {
+                line: blank,
+                reason: "Blank line".to_string(),
+            });
+        }
         if split.len() != 2 {
-            return Err(format!("Line does not contain 2 elements: {}", line));
-        }
-        if split[1] == "X" {
-            score += 1;
-            if split[0] == "A" {
-                score += 3;
-            } else if split[0] == "B" {
-                score += 0;
-            } else if split[0] == "C" {
-                score += 6;
-            } else {
-                return Err(format!("Not A, B, or C: {}", split[0]));
-            }
-        } else if split[1] == "Y" {
-            score += 2;
-            if split[0] == "A" {
-                score += 6;
-            } else if split[0] == "B" {
-                score += 3;
-            } else if split[0] == "C" {
-                score += 0;
-            } else {
-                return Err(format!("Not A, B, or C: {}", split[0]));
-            }
-        } else if split[1] == "Z" {
-            score += 3;
-            if split[0] == "A" {
-                score += 0;
-            } else if split[0] == "B" {
-                score += 6;
-            } else if split[0] == "C" {
-                score += 3;
-            } else {
-                return Err(format!("Not A, B, or C: {}", split[0]));
-            }
-        } else {
-            return Err(format!("Not X, Y, or Z: {}", split[1]));
+            issues.push(GuideIssue {
+                line: i + 1,
+                reason: format!("Line does not contain 2 elements: {}", line.trim_end()),
+            });
+            continue;
         }
+        lines.push(GuideLine {
+            line: i + 1,
+            opponent: split[0].to_string(),
+            column: split[1].to_string(),
+        });
     }
-    Ok(score)
+    Ok((lines, issues))
 }
 
-fn solve_part_two<R: Read>(f: R) -> Result<u32, String> {
-    let mut score = 0;
-    for line in std::io::BufReader::new(f).lines() {
-        let line = match line {
-            Ok(x) => x,
-            Err(e) => return Err(format!("Could not read line: {}", e)),
+/// Reads the guide's columns as the opponent's shape and X, Y or Z, with an issue for each
+/// letter that is neither, alongside those from `read_guide_lines`
+fn read_guide<R: Read>(f: R) -> Result<(Guide, Vec<GuideIssue>), String> {
+    let (lines, mut issues) = read_guide_lines(f)?;
+    let mut guide = Vec::new();
+    for line in lines {
+        let mut issue = |reason| {
+            issues.push(GuideIssue {
+                line: line.line,
+                reason,
+            })
         };
-        let split: Vec<&str> = line.split_whitespace().collect();
-        if split.len() != 2 {
-            return Err(format!("Line does not contain 2 elements: {}", line));
-        }
-        if split[1] == "X" {
-            // Lose
-            score += 0;
-            if split[0] == "A" {
-                // Rock
-                score += 3; //Scissors
-            } else if split[0] == "B" {
-                // Paper
-                score += 1; //Rock
-            } else if split[0] == "C" {
-                // Scissors
-                score += 2; //Paper
-            } else {
-                return Err(format!("Not A, B, or C: {}", split[0]));
-            }
-        } else if split[1] == "Y" {
-            //Draw
-            score += 3;
-            if split[0] == "A" {
-                score += 1;
-            } else if split[0] == "B" {
-                score += 2;
-            } else if split[0] == "C" {
-                score += 3;
-            } else {
-                return Err(format!("Not A, B, or C: {}", split[0]));
-            }
-        } else if split[1] == "Z" {
-            //Win
-            score += 6;
-            if split[0] == "A" {
-                score += 2;
-            } else if split[0] == "B" {
-                score += 3;
-            } else if split[0] == "C" {
-                score += 1;
-            } else {
-                return Err(format!("Not A, B, or C: {}", split[0]));
-            }
-        } else {
-            return Err(format!("Not X, Y, or Z: {}", split[1]));
+        let opponent = line.opponent.parse::<Shape>();
+        if let Err(e) = &opponent {
+            issue(e.clone());
+        }
+        let column = column_index(&line.column);
+        if let Err(e) = &column {
+            issue(e.clone());
+        }
+        if let (Ok(opponent), Ok(_)) = (opponent, column) {
+            guide.push((opponent, line.column));
         }
     }
+    // Issues found while splitting come first, so restore line order
+    issues.sort_by_key(|issue| issue.line);
+    Ok((guide, issues))
+}
+
+/// Every issue on a line of its own, for reporting as a single error
+fn issues_error(issues: &[GuideIssue]) -> String {
+    issues
+        .iter()
+        .map(GuideIssue::to_string)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn parse_guide<R: Read>(f: R) -> Result<Guide, String> {
+    let (guide, issues) = read_guide(f)?;
+    if issues.is_empty() {
+        return Ok(guide);
+    }
+    Err(issues_error(&issues))
+}
+
+fn score_guide(
+    guide: &[(Shape, String)],
+    interpretation: &dyn GuideInterpretation,
+) -> Result<u32, String> {
+    let mut score = 0;
+    for (opponent, column) in guide {
+        score += round_score(*opponent, interpretation.response(*opponent, column)?);
+    }
     Ok(score)
 }
 
-fn main() -> Result<(), String> {
+/// Scores the guide under every interpretation, highest score first
+fn score_all_interpretations(guide: &[(Shape, String)]) -> Result<Vec<(String, u32)>, String> {
+    let mut scores = Vec::new();
+    for interpretation in all_interpretations() {
+        scores.push((
+            interpretation.describe(),
+            score_guide(guide, interpretation.as_ref())?,
+        ));
+    }
+    scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    Ok(scores)
+}
+
+fn solve_part_one<R: Read>(f: R) -> Result<u32, String> {
+    score_guide(&parse_guide(f)?, &ShapeMapping::PUZZLE)
+}
+
+fn solve_part_two<R: Read>(f: R) -> Result<u32, String> {
+    score_guide(&parse_guide(f)?, &OutcomeMapping::PUZZLE)
+}
+
+fn run() -> Result<(), String> {
     let mut f = match std::fs::File::open("./input.txt") {
         Ok(f) => f,
         Err(e) => return Err(format!("Error opening input.txt: {}", e)),
     };
 
+    let (flags, modes): (Vec<String>, Vec<String>) = std::env::args()
+        .skip(1)
+        .partition(|arg| arg.starts_with("--"));
+    let mut csv = false;
+    let mut strict = false;
+    for flag in &flags {
+        match flag.as_str() {
+            "--csv" => csv = true,
+            "--strict" => strict = true,
+            _ => return Err(format!("Unknown flag: {}", flag)),
+        }
+    }
+
+    match modes.first().map(String::as_str) {
+        None => {}
+        Some("mappings") => {
+            let scores = score_all_interpretations(&parse_guide(&f)?)?;
+            for (description, score) in &scores {
+                println!("{:<32} {}", description, score);
+            }
+            if let (Some(best), Some(worst)) = (scores.first(), scores.last()) {
+                println!("Best:  {} ({})", best.0, best.1);
+                println!("Worst: {} ({})", worst.0, worst.1);
+            }
+            return Ok(());
+        }
+        Some("game") => {
+            let game = match modes.get(1).map(String::as_str) {
+                Some("rps") => Game::from_spec(game::ROCK_PAPER_SCISSORS)?,
+                Some("rpsls") => Game::from_spec(game::ROCK_PAPER_SCISSORS_LIZARD_SPOCK)?,
+                Some(spec) => Game::from_spec(spec)?,
+                None => return Err("Usage: game <rps|rpsls|spec>".to_string()),
+            };
+            for shape in game.shapes() {
+                println!(
+                    "{} ({}/{}): {} points",
+                    shape.name, shape.opponent_letter, shape.my_letter, shape.points
+                );
+            }
+            let (lines, issues) = read_guide_lines(&f)?;
+            if !issues.is_empty() {
+                return Err(issues_error(&issues));
+            }
+            println!("Part One: {}", game.score_shapes(&lines)?);
+            println!("Part Two: {}", game.score_outcomes(&lines)?);
+            return Ok(());
+        }
+        Some("transcript") => {
+            let interpretation: &dyn GuideInterpretation = match modes.get(1).map(String::as_str) {
+                None | Some("one") => &ShapeMapping::PUZZLE,
+                Some("two") => &OutcomeMapping::PUZZLE,
+                Some(_) => return Err("Usage: transcript [one|two] [--csv]".to_string()),
+            };
+            let transcript = Transcript::new(&parse_guide(&f)?, interpretation)?;
+            if csv {
+                print!("{}", transcript.to_csv());
+            } else {
+                print!("{}", transcript.to_table());
+            }
+            return Ok(());
+        }
+        Some("validate") => {
+            let (guide, issues) = read_guide(&f)?;
+            for issue in &issues {
+                println!("{}", issue);
+            }
+            println!("{} rounds, {} malformed lines", guide.len(), issues.len());
+            if strict && !issues.is_empty() {
+                return Err(format!("Guide has {} malformed lines", issues.len()));
+            }
+            return Ok(());
+        }
+        Some("counter") => {
+            let constraints = modes[1..].join(" ").parse::<counter::Constraints>()?;
+            let opponents = parse_guide(&f)?
+                .iter()
+                .map(|(opponent, _)| *opponent)
+                .collect::<Vec<Shape>>();
+            let plan = counter::best_response(&opponents, &constraints)?;
+            // The guide goes to stdout on its own so it can be redirected to a file
+            eprintln!("Maximum score: {}", plan.score);
+            print!("{}", counter::to_guide(&opponents, &plan.moves));
+            return Ok(());
+        }
+        Some("simulate") => {
+            let usage = "Usage: simulate [one|two] [matches] [seed]";
+            let interpretation: &dyn GuideInterpretation = match modes.get(1).map(String::as_str) {
+                None | Some("one") => &ShapeMapping::PUZZLE,
+                Some("two") => &OutcomeMapping::PUZZLE,
+                Some(_) => return Err(usage.to_string()),
+            };
+            let matches = match modes.get(2).map(|x| x.parse::<usize>()) {
+                None => 1000,
+                Some(Ok(x)) => x,
+                Some(Err(_)) => return Err(usage.to_string()),
+            };
+            let seed = match modes.get(3).map(|x| x.parse::<u64>()) {
+                None => 1,
+                Some(Ok(x)) => x,
+                Some(Err(_)) => return Err(usage.to_string()),
+            };
+            let transcript = Transcript::new(&parse_guide(&f)?, interpretation)?;
+            let moves = transcript
+                .rounds
+                .iter()
+                .map(|r| r.me)
+                .collect::<Vec<Shape>>();
+            println!("Guide score: {}", transcript.total());
+            let mut rng = simulate::Rng::new(seed);
+            for mut model in simulate::all_models() {
+                let result = simulate::simulate(&moves, model.as_mut(), matches, &mut rng);
+                println!(
+                    "{:<18} mean {:>10.2}  variance {:>12.2}  std dev {:>8.2}",
+                    model.name(),
+                    result.mean,
+                    result.variance,
+                    result.variance.sqrt()
+                );
+            }
+            return Ok(());
+        }
+        Some(mode) => return Err(format!("Unknown mode: {}", mode)),
+    }
+
     println!("Part One: {}", solve_part_one(&f)?);
     if let Err(e) = f.rewind() {
         return Err(format!("Could not rewind file: {}", e));
@@ -124,6 +479,14 @@ fn main() -> Result<(), String> {
     Ok(())
 }
 
+fn main() {
+    // Printed with Display rather than returned so every malformed line gets a line of its own
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
 #[test]
 fn test_input() -> Result<(), String> {
     let input = r#"A Y
@@ -139,3 +502,276 @@ C Z
 
     Ok(())
 }
+
+#[test]
+fn test_rules() -> Result<(), String> {
+    let shapes = [Shape::Rock, Shape::Paper, Shape::Scissors];
+    for shape in shapes {
+        assert_eq!(shape.against(shape), Outcome::Draw);
+        assert_eq!(shape.against(shape.beats()), Outcome::Win);
+        assert_eq!(shape.against(shape.beaten_by()), Outcome::Lose);
+        for outcome in [Outcome::Lose, Outcome::Draw, Outcome::Win] {
+            assert_eq!(outcome.response_to(shape).against(shape), outcome);
+        }
+    }
+    assert_eq!(round_score(Shape::Rock, Shape::Paper), 8);
+    assert_eq!(round_score(Shape::Paper, Shape::Rock), 1);
+    assert_eq!(round_score(Shape::Scissors, Shape::Scissors), 6);
+
+    assert_eq!("B".parse::<Shape>()?, Shape::Paper);
+    assert!("X".parse::<Shape>().is_err());
+    assert_eq!(
+        OutcomeMapping::PUZZLE.response(Shape::Rock, "Z")?,
+        Shape::Paper
+    );
+    assert!(OutcomeMapping::PUZZLE.response(Shape::Rock, "C").is_err());
+    assert_eq!("Y".parse::<Outcome>()?, Outcome::Draw);
+    assert!("A".parse::<Outcome>().is_err());
+    for column in ["X", "Y", "Z"] {
+        assert_eq!(
+            OutcomeMapping::PUZZLE.0[column.parse::<Outcome>()?.index()],
+            column.parse::<Outcome>()?
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_interpretations() -> Result<(), String> {
+    let guide = parse_guide("A Y\nB X\nC Z\n".as_bytes())?;
+
+    let reversed = ShapeMapping([Shape::Scissors, Shape::Paper, Shape::Rock]);
+    assert_eq!(score_guide(&guide, &reversed)?, 8 + 9 + 7);
+    assert_eq!(reversed.describe(), "X=Scissors Y=Paper Z=Rock");
+
+    let scores = score_all_interpretations(&guide)?;
+    assert_eq!(scores.len(), 12);
+    assert!(scores.contains(&("X=Rock Y=Paper Z=Scissors".to_string(), 15)));
+    assert!(scores.contains(&("X=Lose Y=Draw Z=Win".to_string(), 12)));
+    assert_eq!(scores.first().map(|x| x.1), Some(24));
+    assert_eq!(scores.last().map(|x| x.1), Some(6));
+
+    Ok(())
+}
+
+#[test]
+fn test_game() -> Result<(), String> {
+    let rps = Game::from_spec(game::ROCK_PAPER_SCISSORS)?;
+    let shapes = [Shape::Rock, Shape::Paper, Shape::Scissors];
+    for (me, my_shape) in shapes.iter().enumerate() {
+        for (opponent, opponent_shape) in shapes.iter().enumerate() {
+            assert_eq!(
+                rps.round_score(opponent, me),
+                round_score(*opponent_shape, *my_shape)
+            );
+        }
+    }
+    let lines = |input: &str| read_guide_lines(input.as_bytes()).map(|(lines, _)| lines);
+    // The same guide format as every other mode, trailing blank lines included
+    let input = "A Y\r\nB X\r\nC Z\r\n\r\n";
+    assert_eq!(rps.score_shapes(&lines(input)?)?, 15);
+    assert_eq!(rps.score_outcomes(&lines(input)?)?, 12);
+    assert_eq!(solve_part_one(input.as_bytes())?, 15);
+    assert_eq!(solve_part_two(input.as_bytes())?, 12);
+    assert_eq!(
+        rps.score_outcomes(&lines("A Y\nE X\nC W\nD V\n")?),
+        Err("Line 2: Not an opponent shape: E\n\
+             Line 3: Not X, Y, or Z: W\n\
+             Line 4: Not an opponent shape: D\n\
+             Line 4: Not X, Y, or Z: V"
+            .to_string())
+    );
+    assert_eq!(
+        rps.score_shapes(&lines("D V\n")?),
+        Err("Line 1: Not an opponent shape: D\nLine 1: Not one of our shapes: V".to_string())
+    );
+
+    let rpsls = Game::from_spec(game::ROCK_PAPER_SCISSORS_LIZARD_SPOCK)?;
+    let shape = |name: &str| rpsls.shapes().iter().position(|s| s.name == name);
+    let wins = [
+        ("Scissors", "Paper"),
+        ("Paper", "Rock"),
+        ("Rock", "Lizard"),
+        ("Lizard", "Spock"),
+        ("Spock", "Scissors"),
+        ("Scissors", "Lizard"),
+        ("Lizard", "Paper"),
+        ("Paper", "Spock"),
+        ("Spock", "Rock"),
+        ("Rock", "Scissors"),
+    ];
+    for (winner, loser) in wins {
+        let (winner, loser) = (shape(winner).unwrap(), shape(loser).unwrap());
+        assert_eq!(rpsls.outcome(winner, loser), Outcome::Win);
+        assert_eq!(rpsls.outcome(loser, winner), Outcome::Lose);
+    }
+    // Spock (5) beats Rock, as does Paper (2)
+    assert_eq!(rpsls.score_shapes(&lines("A Z\nE V\n")?)?, 11 + 1);
+    assert_eq!(rpsls.score_outcomes(&lines("A Z\nD Y\n")?)?, 11 + 7);
+
+    assert!(Game::from_spec("Rock A X 1, Paper B Y 2").is_err());
+    assert!(Game::from_spec("Rock A X 1, Paper B Y 2, Scissors C Y 3").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_transcript() -> Result<(), String> {
+    let guide = parse_guide("A Y\nB X\nC Z\n".as_bytes())?;
+
+    let transcript = Transcript::new(&guide, &OutcomeMapping::PUZZLE)?;
+    assert_eq!(
+        transcript.rounds[1],
+        transcript::Round {
+            number: 2,
+            opponent: Shape::Paper,
+            me: Shape::Rock,
+            outcome: Outcome::Lose,
+        }
+    );
+    assert_eq!(transcript.count(Outcome::Win), 1);
+    assert_eq!(transcript.count(Outcome::Draw), 1);
+    assert_eq!(transcript.count(Outcome::Lose), 1);
+    assert_eq!(transcript.shape_points(), 1 + 1 + 1);
+    assert_eq!(transcript.outcome_points(), 9);
+    assert_eq!(transcript.total(), 12);
+    assert_eq!(
+        transcript.to_csv().lines().nth(3),
+        Some("3,Scissors,Rock,Win,1,6")
+    );
+    assert!(transcript
+        .to_table()
+        .ends_with("Shape points: 3, Outcome points: 9, Total: 12\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_simulate() -> Result<(), String> {
+    use simulate::OpponentModel;
+
+    let moves = [Shape::Rock, Shape::Rock, Shape::Paper, Shape::Rock];
+
+    // Always playing Rock loses every round after the first
+    let mut rng = simulate::Rng::new(7);
+    let rocks = [Shape::Rock; 4];
+    let result = simulate::simulate(&rocks, &mut simulate::BeatLastMove, 50, &mut rng);
+    assert_eq!(result.matches, 50);
+    assert!((4.0..=10.0).contains(&result.mean));
+
+    let mut counter = simulate::FrequencyCounter::default();
+    assert_eq!(counter.next(Some(Shape::Rock), &mut rng), Shape::Paper);
+    assert_eq!(counter.next(Some(Shape::Scissors), &mut rng), Shape::Paper);
+    assert_eq!(counter.next(Some(Shape::Scissors), &mut rng), Shape::Rock);
+    counter.reset();
+    assert_eq!(counter.next(Some(Shape::Paper), &mut rng), Shape::Scissors);
+
+    // The same seed reproduces the same results
+    let run = |seed| {
+        let mut rng = simulate::Rng::new(seed);
+        simulate::simulate(&moves, &mut simulate::UniformRandom, 200, &mut rng)
+    };
+    assert_eq!(run(3), run(3));
+    let result = run(3);
+    assert!(result.variance > 0.0);
+    // Against a uniform opponent each round averages 3 outcome points on top of our shape
+    assert!((result.mean - 17.0).abs() < 1.0);
+
+    Ok(())
+}
+
+#[test]
+fn test_counter() -> Result<(), String> {
+    let opponents = [Shape::Rock, Shape::Paper, Shape::Scissors, Shape::Rock];
+
+    let plan = counter::best_response(&opponents, &counter::Constraints::default())?;
+    assert_eq!(plan.score, 8 + 9 + 7 + 8);
+    let guide = counter::to_guide(&opponents, &plan.moves);
+    assert_eq!(guide, "A Y\nB Z\nC X\nA Y\n");
+    assert_eq!(solve_part_one(guide.as_bytes())?, plan.score);
+
+    // The draw is best spent against Scissors, where drawing is worth the most
+    let constraints = "wins<=2 draws=1".parse::<counter::Constraints>()?;
+    let plan = counter::best_response(&opponents, &constraints)?;
+    assert_eq!(plan.score, 8 + 9 + 6 + 3);
+    let transcript = Transcript::new(
+        &parse_guide(counter::to_guide(&opponents, &plan.moves).as_bytes())?,
+        &ShapeMapping::PUZZLE,
+    )?;
+    assert_eq!(transcript.count(Outcome::Win), 2);
+    assert_eq!(transcript.count(Outcome::Draw), 1);
+    assert_eq!(transcript.total(), plan.score);
+
+    let constraints = "losses>=3".parse::<counter::Constraints>()?;
+    let plan = counter::best_response(&opponents, &constraints)?;
+    assert_eq!(plan.score, 3 + 9 + 2 + 3);
+
+    let constraints = "wins=3 draws>=2".parse::<counter::Constraints>()?;
+    assert!(counter::best_response(&opponents, &constraints).is_err());
+    assert!("ties<=2".parse::<counter::Constraints>().is_err());
+    assert!("wins~2".parse::<counter::Constraints>().is_err());
+
+    // Bounds past the number of rounds carry no extra constraint
+    let constraints = "wins<=18446744073709551615".parse::<counter::Constraints>()?;
+    assert_eq!(
+        counter::best_response(&opponents, &constraints)?.score,
+        8 + 9 + 7 + 8
+    );
+    let constraints = "draws<=20000 losses<=20000".parse::<counter::Constraints>()?;
+    assert_eq!(
+        counter::best_response(&opponents, &constraints)?.score,
+        8 + 9 + 7 + 8
+    );
+    let constraints = "wins>=1".parse::<counter::Constraints>()?;
+    let plan = counter::best_response(&opponents, &constraints)?;
+    let guide = counter::to_guide(&opponents, &plan.moves);
+    assert_eq!(guide, "A Y\nB Z\nC X\nA Y\n");
+    let constraints = "draws>=5".parse::<counter::Constraints>()?;
+    assert!(counter::best_response(&opponents, &constraints).is_err());
+
+    // An empty guide only satisfies constraints that allow no rounds at all
+    let constraints = "wins<=100000 draws<=100000 losses<=1000".parse::<counter::Constraints>()?;
+    assert_eq!(
+        counter::best_response(&[], &constraints)?,
+        counter::Plan {
+            score: 0,
+            moves: Vec::new()
+        }
+    );
+    let constraints = "wins>=1".parse::<counter::Constraints>()?;
+    assert!(counter::best_response(&[], &constraints).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_validate() -> Result<(), String> {
+    let input = "A Y\r\nB X\r\n\r\nD Z\nC W\nA\nE V\nC Z\n\n\r\n";
+
+    let (guide, issues) = read_guide(input.as_bytes())?;
+    assert_eq!(guide.len(), 3);
+    assert_eq!(
+        issues
+            .iter()
+            .map(GuideIssue::to_string)
+            .collect::<Vec<String>>(),
+        vec![
+            "Line 3: Blank line",
+            "Line 4: Not A, B, or C: D",
+            "Line 5: Not X, Y, or Z: W",
+            "Line 6: Line does not contain 2 elements: A",
+            "Line 7: Not A, B, or C: E",
+            "Line 7: Not X, Y, or Z: V",
+        ]
+    );
+    assert_eq!(
+        parse_guide(input.as_bytes()).unwrap_err().lines().count(),
+        6
+    );
+
+    let input = "A Y\r\nB X\r\nC Z\r\n\r\n";
+    assert_eq!(solve_part_one(input.as_bytes())?, 15);
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::round_score;
+use crate::Shape;
+
+/// Small deterministic generator (SplitMix64) so simulations can be reproduced from a seed
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn shape(&mut self) -> Shape {
+        Shape::ALL[(self.next_u64() % 3) as usize]
+    }
+}
+
+/// An opponent that picks its shapes as the match goes on instead of following the guide
+pub trait OpponentModel {
+    fn name(&self) -> &'static str;
+    /// Forgets everything seen in the previous match
+    fn reset(&mut self);
+    /// The opponent's next shape. `last` is the shape we played in the previous round.
+    fn next(&mut self, last: Option<Shape>, rng: &mut Rng) -> Shape;
+}
+
+/// Plays every shape with equal probability
+pub struct UniformRandom;
+
+/// Expects us to repeat whatever we have played most often so far and plays what beats it
+#[derive(Default)]
+pub struct FrequencyCounter {
+    counts: [usize; 3],
+}
+
+/// Plays whatever would have beaten our previous shape
+pub struct BeatLastMove;
+
+impl OpponentModel for UniformRandom {
+    fn name(&self) -> &'static str {
+        "Uniform random"
+    }
+
+    fn reset(&mut self) {}
+
+    fn next(&mut self, _last: Option<Shape>, rng: &mut Rng) -> Shape {
+        rng.shape()
+    }
+}
+
+impl OpponentModel for FrequencyCounter {
+    fn name(&self) -> &'static str {
+        "Frequency counter"
+    }
+
+    fn reset(&mut self) {
+        self.counts = [0; 3];
+    }
+
+    fn next(&mut self, last: Option<Shape>, rng: &mut Rng) -> Shape {
+        let last = match last {
+            Some(x) => x,
+            None => return rng.shape(),
+        };
+        self.counts[last.index()] += 1;
+        // Ties go to the shape listed first in Shape::ALL
+        let mut expected = Shape::ALL[0];
+        for shape in Shape::ALL {
+            if self.counts[shape.index()] > self.counts[expected.index()] {
+                expected = shape;
+            }
+        }
+        expected.beaten_by()
+    }
+}
+
+impl OpponentModel for BeatLastMove {
+    fn name(&self) -> &'static str {
+        "Beat last move"
+    }
+
+    fn reset(&mut self) {}
+
+    fn next(&mut self, last: Option<Shape>, rng: &mut Rng) -> Shape {
+        match last {
+            Some(x) => x.beaten_by(),
+            None => rng.shape(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Simulation {
+    pub matches: usize,
+    pub mean: f64,
+    pub variance: f64,
+}
+
+/// Plays our fixed sequence of shapes against `opponent` `matches` times and summarizes the
+/// total score of each match. The variance is the population variance over the matches.
+pub fn simulate(
+    moves: &[Shape],
+    opponent: &mut dyn OpponentModel,
+    matches: usize,
+    rng: &mut Rng,
+) -> Simulation {
+    let mut scores = Vec::with_capacity(matches);
+    for _ in 0..matches {
+        opponent.reset();
+        let mut score = 0;
+        let mut last = None;
+        for me in moves {
+            score += round_score(opponent.next(last, rng), *me);
+            last = Some(*me);
+        }
+        scores.push(score as f64);
+    }
+    let count = scores.len().max(1) as f64;
+    let mean = scores.iter().sum::<f64>() / count;
+    let variance = scores.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / count;
+    Simulation {
+        matches,
+        mean,
+        variance,
+    }
+}
+
+pub fn all_models() -> Vec<Box<dyn OpponentModel>> {
+    vec![
+        Box::new(UniformRandom),
+        Box::new(FrequencyCounter::default()),
+        Box::new(BeatLastMove),
+    ]
+}
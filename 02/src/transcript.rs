@@ -0,0 +1,112 @@
+use crate::round_score;
+use crate::GuideInterpretation;
+use crate::Outcome;
+use crate::Shape;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Round {
+    pub number: usize,
+    pub opponent: Shape,
+    pub me: Shape,
+    pub outcome: Outcome,
+}
+
+impl Round {
+    pub fn shape_points(&self) -> u32 {
+        self.me.score()
+    }
+
+    pub fn outcome_points(&self) -> u32 {
+        self.outcome.score()
+    }
+}
+
+/// Every round of a guide played out under one interpretation
+pub struct Transcript {
+    pub rounds: Vec<Round>,
+}
+
+impl Transcript {
+    pub fn new(
+        guide: &[(Shape, String)],
+        interpretation: &dyn GuideInterpretation,
+    ) -> Result<Transcript, String> {
+        let mut rounds = Vec::new();
+        for (i, (opponent, column)) in guide.iter().enumerate() {
+            let me = interpretation.response(*opponent, column)?;
+            rounds.push(Round {
+                number: i + 1,
+                opponent: *opponent,
+                me,
+                outcome: me.against(*opponent),
+            });
+        }
+        Ok(Transcript { rounds })
+    }
+
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.rounds.iter().filter(|r| r.outcome == outcome).count()
+    }
+
+    pub fn shape_points(&self) -> u32 {
+        self.rounds.iter().map(Round::shape_points).sum()
+    }
+
+    pub fn outcome_points(&self) -> u32 {
+        self.rounds.iter().map(Round::outcome_points).sum()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.rounds
+            .iter()
+            .map(|r| round_score(r.opponent, r.me))
+            .sum()
+    }
+
+    pub fn to_table(&self) -> String {
+        let mut out = format!(
+            "{:>5}  {:<8}  {:<8}  {:<7}  {:>5}  {:>7}\n",
+            "Round", "Opponent", "Me", "Outcome", "Shape", "Outcome"
+        );
+        for r in &self.rounds {
+            out += &format!(
+                "{:>5}  {:<8}  {:<8}  {:<7}  {:>5}  {:>7}\n",
+                r.number,
+                format!("{:?}", r.opponent),
+                format!("{:?}", r.me),
+                format!("{:?}", r.outcome),
+                r.shape_points(),
+                r.outcome_points()
+            );
+        }
+        out += &format!(
+            "\nWins: {}, Draws: {}, Losses: {}\n",
+            self.count(Outcome::Win),
+            self.count(Outcome::Draw),
+            self.count(Outcome::Lose)
+        );
+        out += &format!(
+            "Shape points: {}, Outcome points: {}, Total: {}\n",
+            self.shape_points(),
+            self.outcome_points(),
+            self.total()
+        );
+        out
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = "round,opponent,me,outcome,shape_points,outcome_points\n".to_string();
+        for r in &self.rounds {
+            out += &format!(
+                "{},{:?},{:?},{:?},{},{}\n",
+                r.number,
+                r.opponent,
+                r.me,
+                r.outcome,
+                r.shape_points(),
+                r.outcome_points()
+            );
+        }
+        out
+    }
+}
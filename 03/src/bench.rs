@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::item_set::PriorityTable;
+
+fn hashset_priority(item: char) -> Result<u32, String> {
+    if item.is_ascii_lowercase() {
+        Ok(item as u32 - 96)
+    } else if item.is_ascii_uppercase() {
+        Ok(item as u32 + 27 - 65)
+    } else {
+        Err(format!("Invalid char: {}", item))
+    }
+}
+
+/// Part one as it was solved with a `HashSet<char>` per compartment, kept as a baseline
+pub fn hashset_part_one(input: &str) -> Result<u32, String> {
+    let mut total = 0;
+    for line in input.lines() {
+        let chars = line.chars().collect::<Vec<char>>();
+        let (compartment1, compartment2) = chars.split_at(chars.len() / 2);
+        let compartment1 = compartment1.iter().collect::<HashSet<&char>>();
+        for item in compartment2 {
+            if compartment1.contains(item) {
+                total += hashset_priority(*item)?;
+                break;
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Part two as it was solved with a `HashSet<char>` per line, kept as a baseline
+pub fn hashset_part_two(input: &str) -> Result<u32, String> {
+    let lines = input
+        .lines()
+        .map(|line| line.chars().collect::<Vec<char>>())
+        .collect::<Vec<Vec<char>>>();
+    let mut total = 0;
+    for chunk in lines.chunks(3) {
+        let first = chunk[0].iter().collect::<HashSet<&char>>();
+        let second = chunk[1].iter().collect::<HashSet<&char>>();
+        for item in &chunk[2] {
+            if first.contains(item) && second.contains(item) {
+                total += hashset_priority(*item)?;
+                break;
+            }
+        }
+    }
+    Ok(total)
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.next(i + 1));
+        }
+    }
+}
+
+/// Generates `groups` groups of three rucksacks in the puzzle format. Like the puzzle input, each
+/// rucksack has exactly one item type in both compartments and each group exactly one badge.
+pub fn generate_input(groups: usize, seed: u64) -> String {
+    let mut rng = XorShift(seed | 1);
+    let mut letters = ('a'..='z').chain('A'..='Z').collect::<Vec<char>>();
+    let mut out = String::new();
+    for _ in 0..groups {
+        rng.shuffle(&mut letters);
+        let badge = letters[51];
+        // Each elf of the group draws from its own 17 letters, so only the badge is common
+        for (elf, pool) in letters[..51].chunks(17).enumerate() {
+            let shared = pool[0];
+            let len = 8 + rng.next(17);
+            let mut halves = [vec![shared], vec![shared]];
+            halves[elf % 2].push(badge);
+            for (half, items) in halves.iter_mut().enumerate() {
+                let own = &pool[1 + half * 8..9 + half * 8];
+                while items.len() < len {
+                    items.push(own[rng.next(own.len())]);
+                }
+                rng.shuffle(items);
+            }
+            out.extend(halves[0].iter().chain(halves[1].iter()));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn time<F: Fn() -> Result<u32, String>>(runs: usize, f: F) -> Result<(u32, Duration), String> {
+    let start = Instant::now();
+    let mut answer = 0;
+    for _ in 0..runs {
+        answer = f()?;
+    }
+    Ok((answer, start.elapsed() / runs as u32))
+}
+
+fn compare<F, G>(name: &str, runs: usize, item_set: F, hash_set: G) -> Result<String, String>
+where
+    F: Fn() -> Result<u32, String>,
+    G: Fn() -> Result<u32, String>,
+{
+    let (a, item_set_time) = time(runs, item_set)?;
+    let (b, hash_set_time) = time(runs, hash_set)?;
+    if a != b {
+        return Err(format!("{}: ItemSet gave {}, HashSet gave {}", name, a, b));
+    }
+    Ok(format!(
+        "{}: ItemSet {:?}, HashSet {:?} ({:.1}x)\n",
+        name,
+        item_set_time,
+        hash_set_time,
+        hash_set_time.as_secs_f64() / item_set_time.as_secs_f64()
+    ))
+}
+
+/// Times the `ItemSet` solvers against the `HashSet` baseline on generated input
+pub fn run(groups: usize, runs: usize) -> Result<String, String> {
+    let input = generate_input(groups, 0x5EED);
+    let table = PriorityTable::default();
+    let mut out = format!("{} rucksacks, {} runs each\n", groups * 3, runs);
+    out += &compare(
+        "Part One",
+        runs,
+        || crate::solve_part_one(input.as_bytes(), &table),
+        || hashset_part_one(&input),
+    )?;
+    out += &compare(
+        "Part Two",
+        runs,
+        || crate::solve_part_two(input.as_bytes(), &table),
+        || hashset_part_two(&input),
+    )?;
+    Ok(out)
+}
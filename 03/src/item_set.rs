@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+/// Today's rules: `a` to `z` are worth 1 to 26 and `A` to `Z` 27 to 52
+pub const DEFAULT_PRIORITIES: &str = "a-z=1, A-Z=27";
+
+const NO_SLOT: u8 = u8::MAX;
+
+/// Maps item types to priorities. Each item type also gets a slot, its position in the table,
+/// which is the bit used for it in an `ItemSet`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriorityTable {
+    items: Vec<(char, u32)>,
+    // Slots of ASCII items are looked up directly, as they are by far the most common
+    ascii_slots: [u8; 128],
+    slots: HashMap<char, usize>,
+}
+
+impl PriorityTable {
+    /// Parses comma-separated entries of `item=priority`, or `first-last=priority` for a run of
+    /// consecutive characters whose priorities count up from the one given. For example
+    /// `0-9=1, #=20, α-ω=30`.
+    pub fn from_config(config: &str) -> Result<PriorityTable, String> {
+        let mut table = PriorityTable {
+            items: Vec::new(),
+            ascii_slots: [NO_SLOT; 128],
+            slots: HashMap::new(),
+        };
+        for entry in config.split(',') {
+            let entry = entry.trim();
+            let (key, priority) = match entry.rsplit_once('=') {
+                Some(x) => x,
+                None => return Err(format!("Priority entry has no '=': {}", entry)),
+            };
+            let priority = match priority.trim().parse::<u32>() {
+                Ok(x) => x,
+                Err(e) => return Err(format!("Could not parse priority: {}, {}", entry, e)),
+            };
+            let key = key.trim().chars().collect::<Vec<char>>();
+            let run = match key[..] {
+                [item] => item..=item,
+                [first, '-', last] if first <= last => first..=last,
+                _ => return Err(format!("Not an item or run of items: {}", entry)),
+            };
+            for (i, item) in run.enumerate() {
+                let priority = u32::try_from(i).ok().and_then(|i| priority.checked_add(i));
+                match priority {
+                    Some(x) => table.insert(item, x)?,
+                    None => return Err(format!("Priority too large for {}: {}", item, entry)),
+                }
+            }
+        }
+        Ok(table)
+    }
+
+    fn insert(&mut self, item: char, priority: u32) -> Result<(), String> {
+        if self.slots.contains_key(&item) {
+            return Err(format!("Item listed twice: {}", item));
+        }
+        if self.items.len() == u64::BITS as usize {
+            return Err(format!("More than {} item types", u64::BITS));
+        }
+        if item.is_ascii() {
+            self.ascii_slots[item as usize] = self.items.len() as u8;
+        }
+        self.slots.insert(item, self.items.len());
+        self.items.push((item, priority));
+        Ok(())
+    }
+
+    fn slot(&self, item: char) -> Result<usize, String> {
+        if item.is_ascii() && self.ascii_slots[item as usize] != NO_SLOT {
+            return Ok(self.ascii_slots[item as usize] as usize);
+        }
+        match self.slots.get(&item) {
+            Some(x) => Ok(*x),
+            None => Err(format!("Invalid char: {}", item)),
+        }
+    }
+
+    pub fn priority(&self, item: char) -> Result<u32, String> {
+        Ok(self.items[self.slot(item)?].1)
+    }
+}
+
+impl Default for PriorityTable {
+    fn default() -> Self {
+        PriorityTable::from_config(DEFAULT_PRIORITIES).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+/// A set of item types, stored as a bitmask over the slots of a `PriorityTable`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    pub fn single(item: char, table: &PriorityTable) -> Result<ItemSet, String> {
+        Ok(ItemSet(1 << table.slot(item)?))
+    }
+
+    pub fn from_items(items: &str, table: &PriorityTable) -> Result<ItemSet, String> {
+        items.chars().try_fold(ItemSet::default(), |set, item| {
+            Ok(set.union(ItemSet::single(item, table)?))
+        })
+    }
+
+    pub fn union(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    pub fn contains(&self, item: char, table: &PriorityTable) -> bool {
+        table.slot(item).is_ok_and(|slot| self.0 & (1 << slot) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Items with their priorities, in the order they are listed in `table`
+    pub fn iter<'a>(&self, table: &'a PriorityTable) -> impl Iterator<Item = (char, u32)> + 'a {
+        let bits = self.0;
+        table
+            .items
+            .iter()
+            .enumerate()
+            .filter(move |(slot, _)| bits & (1 << slot) != 0)
+            .map(|(_, item)| *item)
+    }
+
+    /// Items in the order they are listed in `table`
+    pub fn items(&self, table: &PriorityTable) -> String {
+        self.iter(table).map(|(item, _)| item).collect()
+    }
+
+    /// Sum of the priorities of every item in the set
+    pub fn priority(&self, table: &PriorityTable) -> Result<u32, String> {
+        self.iter(table).try_fold(0u32, |total, (_, priority)| {
+            total
+                .checked_add(priority)
+                .ok_or_else(|| format!("Priority total overflowed: {}", self.items(table)))
+        })
+    }
+}
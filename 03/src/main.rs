@@ -2,100 +2,332 @@ use std::io::BufRead;
 use std::io::Read;
 use std::io::Seek;
 
-fn solve_part_one<R: Read>(f: R) -> Result<u32, String> {
+mod bench;
+mod item_set;
+mod rebalance;
+
+use item_set::ItemSet;
+use item_set::PriorityTable;
+use rebalance::Plan;
+
+/// Splits a rucksack into its two compartments
+fn compartments(line: &str) -> Result<(&str, &str), String> {
+    let len = line.chars().count();
+    if !len.is_multiple_of(2) {
+        return Err(format!(
+            "Rucksack does not have an even number of items: {}",
+            line
+        ));
+    }
+    let mid = line
+        .char_indices()
+        .nth(len / 2)
+        .map_or(line.len(), |(i, _)| i);
+    Ok(line.split_at(mid))
+}
+
+/// Which items of a rucksack's compartments count towards part one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum DuplicatePolicy {
+    /// The first shared item found in the second compartment
+    #[default]
+    First,
+    /// Every distinct shared item
+    AllDistinct,
+    /// Error on any rucksack with more than one shared item type
+    RejectMultiple,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RucksackAudit {
+    line: usize,
+    compartments: (String, String),
+    shared: ItemSet,
+}
+
+impl RucksackAudit {
+    fn score(&self, policy: DuplicatePolicy, table: &PriorityTable) -> Result<u32, String> {
+        match policy {
+            DuplicatePolicy::First => first_shared(&self.compartments.1, self.shared, table),
+            DuplicatePolicy::AllDistinct => self.shared.priority(table),
+            DuplicatePolicy::RejectMultiple => {
+                if self.shared.len() > 1 {
+                    return Err(format!(
+                        "Rucksack on line {} has {} shared items: {}",
+                        self.line,
+                        self.shared.len(),
+                        self.shared.items(table)
+                    ));
+                }
+                self.shared.priority(table)
+            }
+        }
+    }
+}
+
+fn add_priority(total: u32, priority: u32) -> Result<u32, String> {
+    match total.checked_add(priority) {
+        Some(x) => Ok(x),
+        None => Err("Priority total overflowed".to_string()),
+    }
+}
+
+/// Priority of the first item of `compartment` that is in `shared`, or 0 if there's none
+fn first_shared(compartment: &str, shared: ItemSet, table: &PriorityTable) -> Result<u32, String> {
+    let first = compartment.chars().find(|c| shared.contains(*c, table));
+    first.map_or(Ok(0), |item| table.priority(item))
+}
+
+fn audit_rucksacks<R: Read>(f: R, table: &PriorityTable) -> Result<Vec<RucksackAudit>, String> {
+    let mut audits = Vec::new();
+    for (i, line) in std::io::BufReader::new(f).lines().enumerate() {
+        let line = match line {
+            Ok(x) => x,
+            Err(e) => return Err(format!("Could not read line: {}", e)),
+        };
+        let (compartment1, compartment2) = compartments(&line)?;
+        let shared = ItemSet::from_items(compartment1, table)?
+            .intersection(ItemSet::from_items(compartment2, table)?);
+        audits.push(RucksackAudit {
+            line: i + 1,
+            compartments: (compartment1.to_string(), compartment2.to_string()),
+            shared,
+        });
+    }
+    Ok(audits)
+}
+
+fn score_audits(
+    audits: &[RucksackAudit],
+    policy: DuplicatePolicy,
+    table: &PriorityTable,
+) -> Result<u32, String> {
+    let mut total = 0;
+    for audit in audits {
+        total = add_priority(total, audit.score(policy, table)?)?;
+    }
+    Ok(total)
+}
+
+fn solve_part_one<R: Read>(f: R, table: &PriorityTable) -> Result<u32, String> {
     let mut total = 0;
     for line in std::io::BufReader::new(f).lines() {
         let line = match line {
             Ok(x) => x,
             Err(e) => return Err(format!("Could not read line: {}", e)),
         };
-        let chars = line.chars().collect::<Vec<char>>();
-        if chars.len() % 2 != 0 {
-            return Err(format!(
-                "Rucksack does not have an even number of items: {}",
-                line
-            ));
-        }
-        let (compartment1, compartment2) = chars.split_at(chars.len() / 2);
-        let compartment1 = compartment1
-            .to_owned()
-            .into_iter()
-            .collect::<std::collections::HashSet<char>>();
-        for item in compartment2 {
-            if compartment1.contains(item) {
-                let priority = if *item >= 'a' && *item <= 'z' {
-                    *item as u32 - 96
-                } else if *item >= 'A' && *item <= 'Z' {
-                    *item as u32 + 27 - 65
-                } else {
-                    return Err(format!("Invalid char: {}", item));
-                };
-                total += priority;
-                break;
-            }
-        }
+        let (compartment1, compartment2) = compartments(&line)?;
+        let shared = ItemSet::from_items(compartment1, table)?
+            .intersection(ItemSet::from_items(compartment2, table)?);
+        total = add_priority(total, first_shared(compartment2, shared, table)?)?;
     }
-
     Ok(total)
 }
 
-fn solve_part_two<R: Read>(f: R) -> Result<u32, String> {
-    let mut lines = Vec::new();
+fn read_rucksacks<R: Read>(f: R, table: &PriorityTable) -> Result<Vec<ItemSet>, String> {
+    let mut rucksacks = Vec::new();
     for line in std::io::BufReader::new(f).lines() {
         let line = match line {
             Ok(x) => x,
             Err(e) => return Err(format!("Could not read line: {}", e)),
         };
-        lines.push(line.chars().collect::<Vec<char>>())
+        rucksacks.push(ItemSet::from_items(&line, table)?)
     }
+    Ok(rucksacks)
+}
 
-    let mut total = 0;
-    for chunk in lines.chunks(3) {
-        if chunk.len() != 3 {
-            return Err(format!("lines is not multiple of 3"));
+/// The item types carried by every elf of each group of `group_size` consecutive rucksacks
+fn common_items(rucksacks: &[ItemSet], group_size: usize) -> Result<Vec<ItemSet>, String> {
+    if group_size == 0 {
+        return Err("Group size must be at least 1".to_string());
+    }
+    let mut groups = Vec::new();
+    for chunk in rucksacks.chunks(group_size) {
+        if chunk.len() != group_size {
+            return Err(format!("lines is not multiple of {}", group_size));
         }
-        let first = chunk[0]
-            .to_owned()
-            .into_iter()
-            .collect::<std::collections::HashSet<char>>();
-        let second = chunk[1]
-            .to_owned()
-            .into_iter()
-            .collect::<std::collections::HashSet<char>>();
-        for item in &chunk[2] {
-            if first.contains(&item) && second.contains(&item) {
-                let priority = if *item >= 'a' && *item <= 'z' {
-                    *item as u32 - 96
-                } else if *item >= 'A' && *item <= 'Z' {
-                    *item as u32 + 27 - 65
-                } else {
-                    return Err(format!("Invalid char: {}", item));
-                };
-                total += priority;
-                break;
-            }
+        let common = chunk.iter().copied().reduce(ItemSet::intersection);
+        groups.push(common.unwrap_or_default());
+    }
+    Ok(groups)
+}
+
+/// Sums the badge priority of every group of `group_size` consecutive rucksacks. As in the
+/// original solution, a group with several candidate badges scores the first one found in its
+/// last rucksack, and a group without any scores nothing; the `groups` mode lists both kinds.
+fn solve_groups<R: Read>(f: R, group_size: usize, table: &PriorityTable) -> Result<u32, String> {
+    let mut lines = Vec::new();
+    for line in std::io::BufReader::new(f).lines() {
+        match line {
+            Ok(x) => lines.push(x),
+            Err(e) => return Err(format!("Could not read line: {}", e)),
         }
     }
+    let mut rucksacks = Vec::with_capacity(lines.len());
+    for line in &lines {
+        rucksacks.push(ItemSet::from_items(line, table)?);
+    }
+    let groups = common_items(&rucksacks, group_size)?;
 
+    let mut total = 0;
+    for (common, chunk) in groups.iter().zip(lines.chunks(group_size)) {
+        let badge = chunk[group_size - 1]
+            .chars()
+            .find(|c| common.contains(*c, table));
+        total = add_priority(total, badge.map_or(Ok(0), |item| table.priority(item))?)?;
+    }
     Ok(total)
 }
 
+fn solve_part_two<R: Read>(f: R, table: &PriorityTable) -> Result<u32, String> {
+    solve_groups(f, 3, table)
+}
+
+/// Lists every group's candidate badges, flagging groups that don't have exactly one
+fn group_report(groups: &[ItemSet], group_size: usize, table: &PriorityTable) -> String {
+    let mut out = String::new();
+    let mut flagged = 0;
+    for (i, common) in groups.iter().enumerate() {
+        let first_line = i * group_size + 1;
+        let last_line = first_line + group_size - 1;
+        let items = common.items(table);
+        let badges = match common.len() {
+            0 => "no badge".to_string(),
+            1 => items,
+            _ => format!("{} <- several candidate badges", items),
+        };
+        if common.len() != 1 {
+            flagged += 1;
+        }
+        out += &format!(
+            "Group {} (lines {}-{}): {}\n",
+            i + 1,
+            first_line,
+            last_line,
+            badges
+        );
+    }
+    out += &format!("{} of {} groups flagged\n", flagged, groups.len());
+    out
+}
+
+/// Describes the swaps for every rucksack that needs them, alongside the rebalanced input.
+/// Rucksacks that cannot be rebalanced are reported and left as they were.
+fn rebalance_report(audits: &[RucksackAudit]) -> (String, String) {
+    let mut report = String::new();
+    let mut rewritten = String::new();
+    let (mut swapped, mut impossible) = (0, 0);
+    for audit in audits {
+        let (plan, line) = rebalance::rebalance(&audit.compartments.0, &audit.compartments.1);
+        match plan {
+            Plan::Balanced => {}
+            Plan::Swaps(swaps) => {
+                swapped += 1;
+                let swaps = swaps
+                    .iter()
+                    .map(|swap| format!("{}<->{}", swap.left, swap.right))
+                    .collect::<Vec<String>>();
+                report += &format!("Line {}: swap {}\n", audit.line, swaps.join(", "));
+            }
+            Plan::Impossible => {
+                impossible += 1;
+                report += &format!("Line {}: impossible\n", audit.line);
+            }
+        }
+        rewritten += &line;
+        rewritten.push('\n');
+    }
+    report += &format!(
+        "{} of {} rucksacks rebalanced, {} impossible\n",
+        swapped,
+        audits.len(),
+        impossible
+    );
+    (report, rewritten)
+}
+
 fn main() -> Result<(), String> {
     let mut f = match std::fs::File::open("./input.txt") {
         Ok(f) => f,
         Err(e) => return Err(format!("Error opening input.txt: {}", e)),
     };
 
-    println!("Part One: {}", solve_part_one(&f)?);
+    let (flags, modes): (Vec<String>, Vec<String>) = std::env::args()
+        .skip(1)
+        .partition(|arg| arg.starts_with("--"));
+    let mut table = PriorityTable::default();
+    for flag in &flags {
+        match flag.split_once('=') {
+            Some(("--priorities", config)) => table = PriorityTable::from_config(config)?,
+            _ => return Err(format!("Unknown flag: {}", flag)),
+        }
+    }
+
+    match modes.first().map(String::as_str) {
+        None => {}
+        Some("bench") => {
+            let groups = match modes.get(1).map(|x| x.parse::<usize>()) {
+                None => 100_000,
+                Some(Ok(x)) => x,
+                Some(Err(e)) => return Err(format!("Could not parse group count: {}", e)),
+            };
+            print!("{}", bench::run(groups, 5)?);
+            return Ok(());
+        }
+        Some("audit") => {
+            let policy = match modes.get(1).map(String::as_str) {
+                None | Some("first") => DuplicatePolicy::First,
+                Some("all") => DuplicatePolicy::AllDistinct,
+                Some("reject") => DuplicatePolicy::RejectMultiple,
+                Some(_) => return Err("Usage: audit [first|all|reject]".to_string()),
+            };
+            let audits = audit_rucksacks(&f, &table)?;
+            for audit in &audits {
+                println!(
+                    "Line {}: {} | {} shared {} ({})",
+                    audit.line,
+                    audit.compartments.0,
+                    audit.compartments.1,
+                    audit.shared.items(&table),
+                    audit.shared.priority(&table)?
+                );
+            }
+            println!("Total: {}", score_audits(&audits, policy, &table)?);
+            return Ok(());
+        }
+        Some("groups") => {
+            let group_size = match modes.get(1).map(|x| x.parse::<usize>()) {
+                None => 3,
+                Some(Ok(x)) => x,
+                Some(Err(e)) => return Err(format!("Could not parse group size: {}", e)),
+            };
+            let groups = common_items(&read_rucksacks(&f, &table)?, group_size)?;
+            print!("{}", group_report(&groups, group_size, &table));
+            return Ok(());
+        }
+        Some("rebalance") => {
+            let path = modes.get(1).map_or("./rebalanced.txt", String::as_str);
+            let (report, rewritten) = rebalance_report(&audit_rucksacks(&f, &table)?);
+            print!("{}", report);
+            if let Err(e) = std::fs::write(path, rewritten) {
+                return Err(format!("Error writing {}: {}", path, e));
+            }
+            return Ok(());
+        }
+        Some(mode) => return Err(format!("Unknown mode: {}", mode)),
+    }
+
+    println!("Part One: {}", solve_part_one(&f, &table)?);
     if let Err(e) = f.rewind() {
         return Err(format!("Could not rewind file: {}", e));
     }
-    println!("Part Two: {}", solve_part_two(&f)?);
+    println!("Part Two: {}", solve_part_two(&f, &table)?);
     Ok(())
 }
 
 #[test]
 fn test_input() -> Result<(), String> {
+    let table = PriorityTable::default();
     let input = r#"vJrwpWtwJgWrhcsFMMfFFhFp
 jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
 PmmdzqPrVvPwwTWBwg
@@ -103,11 +335,187 @@ wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
 ttgJtRGJQctTZtZT
 CrZsJsPPZsGzwwsLwLmpwMDw"#;
 
-    let ans = solve_part_one(input.as_bytes())?;
+    let ans = solve_part_one(input.as_bytes(), &table)?;
     assert_eq!(ans, 157);
 
-    let ans = solve_part_two(input.as_bytes())?;
+    let ans = solve_part_two(input.as_bytes(), &table)?;
     assert_eq!(ans, 70);
 
     Ok(())
 }
+
+#[test]
+fn test_item_set() -> Result<(), String> {
+    let table = PriorityTable::default();
+    let set = ItemSet::from_items("aAzZbb", &table)?;
+    assert_eq!(set.items(&table), "abzAZ");
+    assert_eq!(set.priority(&table)?, 1 + 2 + 26 + 27 + 52);
+    let other = ItemSet::from_items("bcZ", &table)?;
+    assert_eq!(set.intersection(other).items(&table), "bZ");
+    assert_eq!(set.union(other).items(&table), "abczAZ");
+    assert_eq!(ItemSet::default().priority(&table)?, 0);
+    assert!(ItemSet::from_items("ab1", &table).is_err());
+
+    let input = bench::generate_input(200, 42);
+    assert_eq!(
+        solve_part_one(input.as_bytes(), &table)?,
+        bench::hashset_part_one(&input)?
+    );
+    assert_eq!(
+        solve_part_two(input.as_bytes(), &table)?,
+        bench::hashset_part_two(&input)?
+    );
+
+    // Several shared items per rucksack and badges per group still score the first one found
+    let input = "abab\nabab\nabab\nzAyBzAyB\nyBxCyBxC\nCyBzCyBz\n";
+    assert_eq!(solve_part_two(input.as_bytes(), &table)?, 1 + 25);
+    assert_eq!(
+        solve_part_two(input.as_bytes(), &table)?,
+        bench::hashset_part_two(input)?
+    );
+    assert_eq!(
+        solve_part_one(input.as_bytes(), &table)?,
+        bench::hashset_part_one(input)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_groups() -> Result<(), String> {
+    let table = PriorityTable::default();
+    let input = "abcX\nbcdX\nbceY\nfg\nhi\n";
+
+    assert_eq!(
+        solve_groups(input.as_bytes(), 1, &table)?,
+        1 + 2 + 2 + 6 + 8
+    );
+    assert!(solve_groups(input.as_bytes(), 2, &table).is_err());
+    assert!(solve_groups(input.as_bytes(), 0, &table).is_err());
+
+    let groups = common_items(&read_rucksacks(input.as_bytes(), &table)?, 5)?;
+    assert_eq!(groups, vec![ItemSet::default()]);
+
+    let rucksacks = read_rucksacks("abcX\nbcdX\nbceY\nfg\nfh\n".as_bytes(), &table)?;
+    let groups = common_items(&rucksacks[..4], 2)?;
+    assert_eq!(
+        group_report(&groups, 2, &table),
+        "Group 1 (lines 1-2): bcX <- several candidate badges\n\
+         Group 2 (lines 3-4): no badge\n\
+         2 of 2 groups flagged\n"
+    );
+    let groups = common_items(&rucksacks[..3], 3)?;
+    assert_eq!(groups[0].items(&table), "bc");
+    // bcX is ambiguous and scores X, found first in Xbcd; bceY and fg share nothing
+    assert_eq!(
+        solve_groups("abcX\nXbcd\nbceY\nfg\n".as_bytes(), 2, &table)?,
+        50
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_audit() -> Result<(), String> {
+    let table = PriorityTable::default();
+    let input = "abcXbcaY\nvJrwpWtwJgWrhcsFMMfFFhFp\nabcd\n";
+
+    let audits = audit_rucksacks(input.as_bytes(), &table)?;
+    assert_eq!(audits[0].line, 1);
+    assert_eq!(
+        audits[0].compartments,
+        ("abcX".to_string(), "bcaY".to_string())
+    );
+    assert_eq!(audits[0].shared.items(&table), "abc");
+    assert_eq!(audits[0].shared.priority(&table)?, 6);
+    assert_eq!(audits[2].shared, ItemSet::default());
+
+    let score = |audits, policy| score_audits(audits, policy, &table);
+    assert_eq!(score(&audits, DuplicatePolicy::First)?, 2 + 16);
+    assert_eq!(score(&audits, DuplicatePolicy::AllDistinct)?, 6 + 16);
+    assert_eq!(
+        score(&audits, DuplicatePolicy::RejectMultiple),
+        Err("Rucksack on line 1 has 3 shared items: abc".to_string())
+    );
+    assert_eq!(score(&audits[1..], DuplicatePolicy::RejectMultiple)?, 16);
+
+    Ok(())
+}
+
+#[test]
+fn test_priority_table() -> Result<(), String> {
+    let table = PriorityTable::from_config("0-9=1, #=20, -=21, α-γ=100")?;
+    assert_eq!(table.priority('0')?, 1);
+    assert_eq!(table.priority('9')?, 10);
+    assert_eq!(table.priority('#')?, 20);
+    assert_eq!(table.priority('-')?, 21);
+    assert_eq!(table.priority('β')?, 101);
+    assert!(table.priority('a').is_err());
+
+    let input = "12#β#3\n9-γ-0α\n";
+    assert_eq!(solve_part_one(input.as_bytes(), &table)?, 20 + 21);
+    let set = ItemSet::from_items("γ#0", &table)?;
+    assert_eq!(set.items(&table), "0#γ");
+    assert_eq!(set.priority(&table)?, 1 + 20 + 102);
+
+    assert_eq!(
+        PriorityTable::from_config(item_set::DEFAULT_PRIORITIES)?,
+        PriorityTable::default()
+    );
+    assert!(PriorityTable::from_config("a=1, a=2").is_err());
+    assert!(PriorityTable::from_config("z-a=1").is_err());
+    assert!(PriorityTable::from_config("ab=1").is_err());
+    assert!(PriorityTable::from_config("a").is_err());
+    assert!(PriorityTable::from_config("\u{100}-\u{200}=1").is_err());
+    assert_eq!(
+        PriorityTable::from_config("a-z=4294967295"),
+        Err("Priority too large for b: a-z=4294967295".to_string())
+    );
+
+    // Totals fail instead of wrapping when the priorities are large
+    let table = PriorityTable::from_config("a=4294967295, b=1")?;
+    assert!(ItemSet::from_items("ab", &table)?.priority(&table).is_err());
+    let audits = audit_rucksacks("abab\n".as_bytes(), &table)?;
+    assert!(score_audits(&audits, DuplicatePolicy::AllDistinct, &table).is_err());
+    assert!(solve_part_one("aa\nbb\n".as_bytes(), &table).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rebalance() -> Result<(), String> {
+    use rebalance::Swap;
+
+    let swap = |left, right| Swap { left, right };
+    assert_eq!(
+        rebalance::rebalance("abc", "def"),
+        (Plan::Balanced, "abcdef".to_string())
+    );
+    assert_eq!(
+        rebalance::rebalance("abcd", "aefg"),
+        (Plan::Swaps(vec![swap('a', 'g')]), "gbcdaefa".to_string())
+    );
+    assert_eq!(
+        rebalance::rebalance("abcX", "bcaY"),
+        (
+            Plan::Swaps(vec![swap('a', 'b'), swap('X', 'c')]),
+            "bbccaXaY".to_string()
+        )
+    );
+    assert_eq!(rebalance::rebalance("aab", "bcc").0, Plan::Impossible);
+
+    let table = PriorityTable::default();
+    let audits = audit_rucksacks("abcd\naab\nbcc\nabcdaefg\n".as_bytes(), &table);
+    assert!(audits.is_err());
+    let audits = audit_rucksacks("abcd\naabbcc\nabcdaefg\n".as_bytes(), &table)?;
+    let (report, rewritten) = rebalance_report(&audits);
+    assert_eq!(
+        report,
+        "Line 2: impossible\n\
+         Line 3: swap a<->g\n\
+         1 of 3 rucksacks rebalanced, 1 impossible\n"
+    );
+    assert_eq!(rewritten, "abcd\naabbcc\ngbcdaefa\n");
+
+    Ok(())
+}
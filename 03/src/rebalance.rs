@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+/// Exchange of one item from each compartment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Swap {
+    pub left: char,
+    pub right: char,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Plan {
+    /// No item type is in both compartments
+    Balanced,
+    Swaps(Vec<Swap>),
+    /// No arrangement with equal compartments keeps every item type to one side
+    Impossible,
+}
+
+/// Finds the fewest swaps that leave every item type in a single compartment, and returns the
+/// rucksack as it would be after them.
+///
+/// Each item type ends up wholly on one side. Choosing a side for every type so the left side
+/// still holds half the items is a subset sum, solved over the left compartment's size while
+/// minimizing the number of items that have to cross.
+pub fn rebalance(left: &str, right: &str) -> (Plan, String) {
+    let left = left.chars().collect::<Vec<char>>();
+    let right = right.chars().collect::<Vec<char>>();
+    let mut counts: BTreeMap<char, (usize, usize)> = BTreeMap::new();
+    for item in &left {
+        counts.entry(*item).or_default().0 += 1;
+    }
+    for item in &right {
+        counts.entry(*item).or_default().1 += 1;
+    }
+    let unchanged = left.iter().chain(right.iter()).collect::<String>();
+    if counts.values().all(|(l, r)| *l == 0 || *r == 0) {
+        return (Plan::Balanced, unchanged);
+    }
+
+    let size = left.len();
+    let types = counts.iter().collect::<Vec<(&char, &(usize, usize))>>();
+    // best[n] is the fewest items crossing with n items assigned to the left so far
+    let mut best: Vec<Option<usize>> = vec![None; size + 1];
+    best[0] = Some(0);
+    let mut to_left = Vec::with_capacity(types.len());
+    for (_, (l, r)) in &types {
+        let mut next = vec![None; size + 1];
+        let mut chose_left = vec![false; size + 1];
+        for (n, cost) in best.iter().enumerate() {
+            let cost = match cost {
+                Some(x) => *x,
+                None => continue,
+            };
+            if next[n].is_none_or(|x| cost + l < x) {
+                next[n] = Some(cost + l);
+                chose_left[n] = false;
+            }
+            let with = n + l + r;
+            if with <= size && next[with].is_none_or(|x| cost + r < x) {
+                next[with] = Some(cost + r);
+                chose_left[with] = true;
+            }
+        }
+        best = next;
+        to_left.push(chose_left);
+    }
+    if best[size].is_none() {
+        return (Plan::Impossible, unchanged);
+    }
+
+    let mut goes_left = BTreeMap::new();
+    let mut n = size;
+    for (i, (item, (l, r))) in types.iter().enumerate().rev() {
+        let left_side = to_left[i][n];
+        if left_side {
+            n -= l + r;
+        }
+        goes_left.insert(**item, left_side);
+    }
+
+    let leaving_left = left.iter().filter(|item| !goes_left[*item]).copied();
+    let leaving_right = right.iter().filter(|item| goes_left[*item]).copied();
+    let swaps = leaving_left
+        .zip(leaving_right)
+        .map(|(left, right)| Swap { left, right })
+        .collect::<Vec<Swap>>();
+
+    // Incoming items take the places of the ones that left, so the rest keep their positions
+    let mut incoming_left = swaps.iter().map(|s| s.right);
+    let mut incoming_right = swaps.iter().map(|s| s.left);
+    let mut rewritten = String::new();
+    for item in &left {
+        let item = if goes_left[item] {
+            Some(*item)
+        } else {
+            incoming_left.next()
+        };
+        rewritten.extend(item);
+    }
+    for item in &right {
+        let item = if goes_left[item] {
+            incoming_right.next()
+        } else {
+            Some(*item)
+        };
+        rewritten.extend(item);
+    }
+    (Plan::Swaps(swaps), rewritten)
+}
@@ -0,0 +1,96 @@
+use crate::interval::Interval;
+use crate::interval::Section;
+
+/// Sorted, disjoint and non-adjacent intervals
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IntervalSet<T: Ord> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Section> IntervalSet<T> {
+    /// Merges overlapping and adjacent intervals
+    pub fn new(intervals: &[Interval<T>]) -> IntervalSet<T> {
+        let mut sorted = intervals.to_vec();
+        sorted.sort();
+        let mut merged: Vec<Interval<T>> = Vec::with_capacity(sorted.len());
+        for interval in sorted {
+            match merged
+                .last_mut()
+                .and_then(|last| last.union(&interval).map(|u| (last, u)))
+            {
+                Some((last, union)) => *last = union,
+                None => merged.push(interval),
+            }
+        }
+        IntervalSet { intervals: merged }
+    }
+
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    /// Number of sections covered
+    pub fn len(&self) -> u128 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    /// The sections of `span` not covered
+    pub fn gaps(&self, span: &Interval<T>) -> Vec<Interval<T>> {
+        let mut gaps = vec![*span];
+        for interval in &self.intervals {
+            let rest = match gaps.pop() {
+                Some(x) => x,
+                None => break,
+            };
+            gaps.extend(rest.difference(interval));
+        }
+        gaps
+    }
+}
+
+/// Splits the sections covered by `intervals` into maximal runs covered by the same number of
+/// intervals, in ascending order
+pub fn multiplicity<T: Section>(intervals: &[Interval<T>]) -> Vec<(Interval<T>, usize)> {
+    // An interval ending on the type's maximum never closes, and is cut off at `last` instead
+    let last = match intervals.iter().map(Interval::end).max() {
+        Some(x) => x,
+        None => return Vec::new(),
+    };
+    let mut events = Vec::with_capacity(intervals.len() * 2);
+    for interval in intervals {
+        events.push((interval.begin(), 1));
+        if let Some(after) = interval.end().succ() {
+            events.push((after, -1));
+        }
+    }
+    events.sort();
+
+    let mut runs = Vec::new();
+    let mut depth: i64 = 0;
+    let mut start = events[0].0;
+    for (i, (at, delta)) in events.iter().enumerate() {
+        if i > 0 && events[i - 1].0 != *at {
+            let run = at.pred().and_then(|end| Interval::new(start, end));
+            if let (true, Some(run)) = (depth > 0, run) {
+                push_run(&mut runs, run, depth as usize);
+            }
+            start = *at;
+        }
+        depth += delta;
+    }
+    if let (true, Some(run)) = (depth > 0, Interval::new(start, last)) {
+        push_run(&mut runs, run, depth as usize);
+    }
+    runs
+}
+
+/// Extends the previous run instead when it continues at the same depth
+fn push_run<T: Section>(runs: &mut Vec<(Interval<T>, usize)>, run: Interval<T>, depth: usize) {
+    if let Some((last, last_depth)) = runs.last_mut() {
+        if *last_depth == depth && last.is_adjacent(&run) {
+            *last = Interval::new(last.begin(), run.end()).unwrap_or(*last);
+            return;
+        }
+    }
+    runs.push((run, depth));
+}
@@ -0,0 +1,169 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Display;
+
+use crate::interval::Interval;
+
+/// One range of the input, identified by its line and position on the line, both 1-based
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Node<T: Ord> {
+    pub line: usize,
+    pub position: usize,
+    pub range: Interval<T>,
+}
+
+/// Graph with an edge between every two overlapping ranges of the input, on any lines.
+///
+/// As an interval graph, everything below falls out of sweeping the ranges in order of their
+/// first section: components are runs of chained overlaps, the largest clique is the most ranges
+/// sharing one section, and greedy coloring in that order needs no more colors than that.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverlapGraph<T: Ord> {
+    pub nodes: Vec<Node<T>>,
+    /// Pairs of node indices, lower index first, in ascending order
+    pub edges: Vec<(usize, usize)>,
+    /// Component of each node, numbered from the lowest sections up
+    pub components: Vec<usize>,
+    /// Node indices of a largest clique, in ascending order
+    pub clique: Vec<usize>,
+    /// Color of each node; overlapping nodes never share one
+    pub colors: Vec<usize>,
+}
+
+impl<T: Ord + Copy> OverlapGraph<T> {
+    pub fn new(assignments: &[Vec<Interval<T>>]) -> OverlapGraph<T> {
+        let nodes = assignments
+            .iter()
+            .enumerate()
+            .flat_map(|(i, ranges)| {
+                ranges.iter().enumerate().map(move |(j, range)| Node {
+                    line: i + 1,
+                    position: j + 1,
+                    range: *range,
+                })
+            })
+            .collect::<Vec<Node<T>>>();
+        let mut order = (0..nodes.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|i| (nodes[*i].range.begin(), nodes[*i].range.end()));
+
+        let mut edges = Vec::new();
+        let mut components = vec![0; nodes.len()];
+        let mut colors = vec![0; nodes.len()];
+        let mut color_count = 0;
+        let mut free_colors = BinaryHeap::new();
+        // Nodes covering the current section, by their last section
+        let mut active: BinaryHeap<Reverse<(T, usize)>> = BinaryHeap::new();
+        let (mut best, mut best_at) = (0, None);
+        let mut component = 0;
+        for (k, i) in order.iter().enumerate() {
+            let range = nodes[*i].range;
+            while let Some(Reverse((end, j))) = active.peek().copied() {
+                if end >= range.begin() {
+                    break;
+                }
+                active.pop();
+                free_colors.push(Reverse(colors[j]));
+            }
+            if active.is_empty() && k > 0 {
+                component += 1;
+            }
+            edges.extend(active.iter().map(|Reverse((_, j))| (*j.min(i), *j.max(i))));
+
+            components[*i] = component;
+            colors[*i] = match free_colors.pop() {
+                Some(Reverse(color)) => color,
+                None => {
+                    color_count += 1;
+                    color_count - 1
+                }
+            };
+            active.push(Reverse((range.end(), *i)));
+            if active.len() > best {
+                best = active.len();
+                best_at = Some(range.begin());
+            }
+        }
+        edges.sort();
+
+        let clique = match best_at {
+            Some(at) => (0..nodes.len())
+                .filter(|i| nodes[*i].range.begin() <= at && at <= nodes[*i].range.end())
+                .collect(),
+            None => Vec::new(),
+        };
+        OverlapGraph {
+            nodes,
+            edges,
+            components,
+            clique,
+            colors,
+        }
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components.iter().max().map_or(0, |x| x + 1)
+    }
+
+    pub fn color_count(&self) -> usize {
+        self.colors.iter().max().map_or(0, |x| x + 1)
+    }
+}
+
+impl<T: Ord + Copy + Display> OverlapGraph<T> {
+    pub fn to_json(&self) -> String {
+        let nodes = (0..self.nodes.len())
+            .map(|i| {
+                let node = &self.nodes[i];
+                format!(
+                    "    {{\"id\": {}, \"line\": {}, \"position\": {}, \"begin\": {}, \
+                     \"end\": {}, \"component\": {}, \"color\": {}}}",
+                    i,
+                    node.line,
+                    node.position,
+                    node.range.begin(),
+                    node.range.end(),
+                    self.components[i],
+                    self.colors[i]
+                )
+            })
+            .collect::<Vec<String>>();
+        let edges = self
+            .edges
+            .iter()
+            .map(|(a, b)| format!("[{}, {}]", a, b))
+            .collect::<Vec<String>>();
+        let clique = self
+            .clique
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<String>>();
+        format!(
+            "{{\n  \"nodes\": [\n{}\n  ],\n  \"edges\": [{}],\n  \"components\": {},\n  \
+             \"max_clique\": [{}],\n  \"colors\": {}\n}}\n",
+            nodes.join(",\n"),
+            edges.join(", "),
+            self.component_count(),
+            clique.join(", "),
+            self.color_count()
+        )
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = "graph overlaps {\n".to_string();
+        for (i, node) in self.nodes.iter().enumerate() {
+            out += &format!(
+                "  {} [label=\"{}#{}: {}\", style=filled, fillcolor=\"/set312/{}\"];\n",
+                i,
+                node.line,
+                node.position,
+                node.range,
+                self.colors[i] % 12 + 1
+            );
+        }
+        for (a, b) in &self.edges {
+            out += &format!("  {} -- {};\n", a, b);
+        }
+        out += "}\n";
+        out
+    }
+}
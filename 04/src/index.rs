@@ -0,0 +1,80 @@
+use crate::interval::Interval;
+
+/// Ranges sorted by their first section and laid out as an implicit balanced search tree, each
+/// subtree annotated with the highest last section in it. Queries skip every subtree that ends
+/// too early or starts too late, so typical queries visit `O(k log n)` entries for `k` results,
+/// though degenerate layouts can still visit `O(n)`.
+#[derive(Debug, Clone)]
+pub struct IntervalIndex<T: Ord> {
+    /// Ranges with their position in the slice the index was built from
+    entries: Vec<(Interval<T>, usize)>,
+    /// Highest last section of the subtree rooted at each entry
+    max_end: Vec<T>,
+}
+
+impl<T: Ord + Copy> IntervalIndex<T> {
+    pub fn new(ranges: &[Interval<T>]) -> IntervalIndex<T> {
+        let mut entries = ranges
+            .iter()
+            .copied()
+            .zip(0..)
+            .collect::<Vec<(Interval<T>, usize)>>();
+        entries.sort();
+        let mut max_end = entries.iter().map(|(r, _)| r.end()).collect::<Vec<T>>();
+        annotate(&entries, &mut max_end, 0, entries.len());
+        IntervalIndex { entries, max_end }
+    }
+
+    /// Positions of the ranges containing `section`, in ascending order
+    pub fn stab(&self, section: T) -> Vec<usize> {
+        self.overlapping(&Interval::point(section))
+    }
+
+    /// Positions of the ranges overlapping `query`, in ascending order
+    pub fn overlapping(&self, query: &Interval<T>) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.search(query, 0, self.entries.len(), &mut found);
+        found.sort_unstable();
+        found
+    }
+
+    fn search(&self, query: &Interval<T>, lo: usize, hi: usize, found: &mut Vec<usize>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.max_end[mid] < query.begin() {
+            return;
+        }
+        self.search(query, lo, mid, found);
+        let (range, position) = &self.entries[mid];
+        if range.begin() > query.end() {
+            return;
+        }
+        if range.overlaps(query) {
+            found.push(*position);
+        }
+        self.search(query, mid + 1, hi, found);
+    }
+}
+
+/// Fills in `max_end` for the subtree over `lo..hi`, returning its highest last section
+fn annotate<T: Ord + Copy>(
+    entries: &[(Interval<T>, usize)],
+    max_end: &mut [T],
+    lo: usize,
+    hi: usize,
+) -> Option<T> {
+    if lo >= hi {
+        return None;
+    }
+    let mid = lo + (hi - lo) / 2;
+    let left = annotate(entries, max_end, lo, mid);
+    let right = annotate(entries, max_end, mid + 1, hi);
+    let end = [left, right]
+        .into_iter()
+        .flatten()
+        .fold(entries[mid].0.end(), T::max);
+    max_end[mid] = end;
+    Some(end)
+}
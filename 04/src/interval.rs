@@ -0,0 +1,115 @@
+use core::fmt::Formatter;
+use std::fmt::Display;
+
+/// Closed interval `[begin, end]` over an ordered type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T: Ord> {
+    begin: T,
+    end: T,
+}
+
+/// Discrete section numbers, so that intervals can be measured and split at a neighbour
+pub trait Section: Ord + Copy {
+    fn succ(self) -> Option<Self>;
+    fn pred(self) -> Option<Self>;
+    /// Number of sections in `[self, end]`, with `self <= end`. Wide enough for every section of
+    /// a 64-bit type.
+    fn count_to(self, end: Self) -> u128;
+}
+
+macro_rules! impl_section {
+    ($($t:ty),*) => {
+        $(impl Section for $t {
+            fn succ(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+            fn pred(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+            fn count_to(self, end: Self) -> u128 {
+                (end as i128 - self as i128 + 1) as u128
+            }
+        })*
+    };
+}
+
+impl_section!(u32, i64);
+
+impl<T: Ord + Copy> Interval<T> {
+    /// Returns `None` when `begin > end`
+    pub fn new(begin: T, end: T) -> Option<Self> {
+        if begin > end {
+            return None;
+        }
+        Some(Interval { begin, end })
+    }
+
+    /// The interval of just `section`
+    pub fn point(section: T) -> Self {
+        Interval {
+            begin: section,
+            end: section,
+        }
+    }
+
+    pub fn begin(&self) -> T {
+        self.begin
+    }
+
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    pub fn contains(&self, other: &Interval<T>) -> bool {
+        self.begin <= other.begin && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.begin <= other.end && other.begin <= self.end
+    }
+
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        Interval::new(self.begin.max(other.begin), self.end.min(other.end))
+    }
+}
+
+impl<T: Section> Interval<T> {
+    pub fn len(&self) -> u128 {
+        self.begin.count_to(self.end)
+    }
+
+    /// Whether the intervals don't overlap but have no section between them
+    pub fn is_adjacent(&self, other: &Interval<T>) -> bool {
+        self.end.succ() == Some(other.begin) || other.end.succ() == Some(self.begin)
+    }
+
+    /// The interval covering both, if they overlap or are adjacent
+    pub fn union(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        if !self.overlaps(other) && !self.is_adjacent(other) {
+            return None;
+        }
+        Interval::new(self.begin.min(other.begin), self.end.max(other.end))
+    }
+
+    /// The sections of `self` not in `other`, as up to two intervals in ascending order
+    pub fn difference(&self, other: &Interval<T>) -> Vec<Interval<T>> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+        let before = other
+            .begin
+            .pred()
+            .and_then(|end| Interval::new(self.begin, end));
+        let after = other
+            .end
+            .succ()
+            .and_then(|begin| Interval::new(begin, self.end));
+        before.into_iter().chain(after).collect()
+    }
+}
+
+impl<T: Ord + Display> Display for Interval<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{} - {}", self.begin, self.end)
+    }
+}
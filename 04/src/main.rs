@@ -5,81 +5,96 @@ use std::io::BufReader;
 use std::io::Read;
 use std::io::Seek;
 
-struct Range {
-    begin: u32,
-    end: u32,
-}
+mod coverage;
+mod graph;
+mod index;
+mod interval;
+mod range;
 
-impl Range {
-    fn fully_contains(&self, other: &Range) -> bool {
-        self.begin <= other.begin && other.end <= self.end
-    }
-    fn partially_contains(&self, other: &Range) -> bool {
-        self.begin <= other.begin && other.begin <= self.end
+use coverage::IntervalSet;
+use graph::OverlapGraph;
+use index::IntervalIndex;
+use range::parse_range;
+use range::parse_ranges;
+use range::Range;
+
+/// Reads every line's ranges
+fn read_assignments<R: Read>(f: R) -> Result<Vec<Vec<Range>>, String> {
+    let mut assignments = Vec::new();
+    for (i, line) in BufReader::new(f).lines().enumerate() {
+        let line = match line {
+            Ok(x) => x,
+            Err(e) => return Err(format!("Could not read line: {}", e)),
+        };
+        match parse_ranges(&line) {
+            Ok(ranges) => assignments.push(ranges),
+            Err(e) => return Err(format!("Invalid range on line {}: {}", i + 1, e)),
+        }
     }
+    Ok(assignments)
 }
 
-impl Display for Range {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "{} - {}", self.begin, self.end)
-    }
+/// How the ranges of one line relate to each other, by index into the line
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct LineReport {
+    /// `(container, contained)`; identical ranges are listed once, earliest first
+    containing: Vec<(usize, usize)>,
+    /// Overlapping pairs, with the earlier index first
+    overlapping: Vec<(usize, usize)>,
+    /// The first range that contains every other range, if any
+    cover: Option<usize>,
 }
 
-fn get_range(range_bgn: &str, range_end: &str) -> Result<Range, String> {
-    let begin = match range_bgn.parse::<u32>() {
-        Ok(x) => x,
-        Err(e) => {
-            return Err(format!(
-                "Could not parse str as integer: {}, {}",
-                range_bgn, e
-            ))
+impl LineReport {
+    fn new(ranges: &[Range]) -> LineReport {
+        let mut report = LineReport::default();
+        for (i, a) in ranges.iter().enumerate() {
+            for (j, b) in ranges.iter().enumerate().skip(i + 1) {
+                if a.contains(b) {
+                    report.containing.push((i, j));
+                } else if b.contains(a) {
+                    report.containing.push((j, i));
+                }
+                if a.overlaps(b) {
+                    report.overlapping.push((i, j));
+                }
+            }
         }
-    };
-    let end = match range_end.parse::<u32>() {
-        Ok(x) => x,
-        Err(e) => {
-            return Err(format!(
-                "Could not parse str as integer: {}, {}",
-                range_bgn, e
-            ))
-        }
-    };
-
-    if begin > end {
-        return Err(format!("Invalid range: {} - {}", begin, end));
+        report.cover = (0..ranges.len()).find(|i| ranges.iter().all(|r| ranges[*i].contains(r)));
+        report
     }
-
-    Ok(Range { begin, end })
 }
 
-fn parse_ranges(line: &str) -> Result<(Range, Range), String> {
-    let parts = line.split(',').collect::<Vec<&str>>();
-    if parts.len() != 2 {
-        return Err(format!("Line did not have two parts: {}", line));
-    }
-    let range1 = parts[0].split('-').collect::<Vec<&str>>();
-    if range1.len() != 2 {
-        return Err(format!("Invalid range: {}, {:?}", parts[0], range1));
-    }
-    let range2 = parts[1].split('-').collect::<Vec<&str>>();
-    if range2.len() != 2 {
-        return Err(format!("Invalid range: {}", parts[1]));
+impl Display for LineReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let pairs = |pairs: &[(usize, usize)], sep: &str| {
+            if pairs.is_empty() {
+                "none".to_string()
+            } else {
+                pairs
+                    .iter()
+                    .map(|(a, b)| format!("#{}{}#{}", a + 1, sep, b + 1))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            }
+        };
+        write!(
+            f,
+            "contains {}; overlaps {}; ",
+            pairs(&self.containing, " > "),
+            pairs(&self.overlapping, " & ")
+        )?;
+        match self.cover {
+            Some(i) => write!(f, "#{} covers all", i + 1),
+            None => write!(f, "no range covers all"),
+        }
     }
-    let range1 = get_range(range1[0], range1[1])?;
-    let range2 = get_range(range2[0], range2[1])?;
-    Ok((range1, range2))
 }
 
 fn solve_part_one<R: Read>(f: R) -> Result<u32, String> {
     let mut total = 0;
-    for line in BufReader::new(f).lines() {
-        let line = match line {
-            Ok(x) => x,
-            Err(e) => return Err(format!("Could not read line: {}", e)),
-        };
-
-        let (range1, range2) = parse_ranges(&line)?;
-        if range1.fully_contains(&range2) || range2.fully_contains(&range1) {
+    for ranges in read_assignments(f)? {
+        if !LineReport::new(&ranges).containing.is_empty() {
             total += 1;
         }
     }
@@ -89,14 +104,8 @@ fn solve_part_one<R: Read>(f: R) -> Result<u32, String> {
 
 fn solve_part_two<R: Read>(f: R) -> Result<u32, String> {
     let mut total = 0;
-    for line in BufReader::new(f).lines() {
-        let line = match line {
-            Ok(x) => x,
-            Err(e) => return Err(format!("Could not read line: {}", e)),
-        };
-
-        let (range1, range2) = parse_ranges(&line)?;
-        if range1.partially_contains(&range2) || range2.partially_contains(&range1) {
+    for ranges in read_assignments(f)? {
+        if !LineReport::new(&ranges).overlapping.is_empty() {
             total += 1;
         }
     }
@@ -104,12 +113,180 @@ fn solve_part_two<R: Read>(f: R) -> Result<u32, String> {
     Ok(total)
 }
 
-fn main() -> Result<(), String> {
+/// Describes how two ranges relate to each other
+fn describe_pair(range1: &Range, range2: &Range) -> String {
+    let show = |range: Option<Range>| range.map_or("none".to_string(), |r| r.to_string());
+    let difference = |a: &Range, b: &Range| {
+        let parts = a.difference(b);
+        if parts.is_empty() {
+            "none".to_string()
+        } else {
+            parts
+                .iter()
+                .map(Range::to_string)
+                .collect::<Vec<String>>()
+                .join(", ")
+        }
+    };
+    let overlap = range1.intersection(range2);
+    let adjacent = if range1.is_adjacent(range2) {
+        ", adjacent"
+    } else {
+        ""
+    };
+    format!(
+        "{} ({}) and {} ({}): overlap {} ({}), union {}, only first {}, only second {}{}",
+        range1,
+        range1.len(),
+        range2,
+        range2.len(),
+        show(overlap),
+        overlap.map_or(0, |r| r.len()),
+        show(range1.union(range2)),
+        difference(range1, range2),
+        difference(range2, range1),
+        adjacent
+    )
+}
+
+/// Summarizes how all ranges together cover the sections, looking for gaps within `span`, or
+/// between the lowest and highest section assigned when no span is given
+fn coverage_report(ranges: &[Range], span: Option<Range>) -> String {
+    let covered = IntervalSet::new(ranges);
+    let show = |ranges: &[Range]| {
+        if ranges.is_empty() {
+            "none".to_string()
+        } else {
+            ranges
+                .iter()
+                .map(Range::to_string)
+                .collect::<Vec<String>>()
+                .join(", ")
+        }
+    };
+    let mut out = format!(
+        "Covered: {} sections in {}\n",
+        covered.len(),
+        show(covered.intervals())
+    );
+
+    let repeated = coverage::multiplicity(ranges)
+        .into_iter()
+        .filter(|(_, depth)| *depth > 1)
+        .collect::<Vec<(Range, usize)>>();
+    out += &format!(
+        "Covered more than once: {} sections\n",
+        repeated.iter().map(|(run, _)| run.len()).sum::<u128>()
+    );
+    for (run, depth) in &repeated {
+        out += &format!("  {} x{}\n", run, depth);
+    }
+
+    let intervals = covered.intervals();
+    let span = span.or_else(|| {
+        let first = intervals.first()?;
+        let last = intervals.last()?;
+        Range::new(first.begin(), last.end())
+    });
+    if let Some(span) = span {
+        out += &format!("Gaps in {}: {}\n", span, show(&covered.gaps(&span)));
+    }
+    out
+}
+
+/// Answers one query against the index: a section such as `5` lists the ranges containing it,
+/// and any other range such as `3-7` lists the ranges overlapping it
+fn answer_query(
+    index: &IntervalIndex<i64>,
+    labels: &[String],
+    query: &str,
+) -> Result<String, String> {
+    let query = query.trim();
+    let range = parse_range(query)?;
+    let found = match range.len() {
+        1 => index.stab(range.begin()),
+        _ => index.overlapping(&range),
+    };
+    let found = found
+        .iter()
+        .map(|i| labels[*i].as_str())
+        .collect::<Vec<&str>>();
+    Ok(format!("{}: {} [{}]", query, found.len(), found.join(", ")))
+}
+
+fn run() -> Result<(), String> {
     let mut f = match std::fs::File::open("./input.txt") {
         Ok(f) => f,
         Err(e) => return Err(format!("Error opening input.txt: {}", e)),
     };
 
+    let modes = std::env::args().skip(1).collect::<Vec<String>>();
+    match modes.first().map(String::as_str) {
+        None => {}
+        Some("pairs") => {
+            for (i, ranges) in read_assignments(&f)?.iter().enumerate() {
+                for (j, a) in ranges.iter().enumerate() {
+                    for (k, b) in ranges.iter().enumerate().skip(j + 1) {
+                        let pair = describe_pair(a, b);
+                        println!("Line {}, #{} and #{}: {}", i + 1, j + 1, k + 1, pair);
+                    }
+                }
+            }
+            return Ok(());
+        }
+        Some("roster") => {
+            for (i, ranges) in read_assignments(&f)?.iter().enumerate() {
+                let report = LineReport::new(ranges);
+                println!("Line {}: {}", i + 1, report);
+            }
+            return Ok(());
+        }
+        Some("coverage") => {
+            let span = match modes.get(1).map(|x| parse_range(x)) {
+                None => None,
+                Some(Ok(range)) => Some(range),
+                Some(Err(e)) => return Err(format!("Could not parse span: {}", e)),
+            };
+            let ranges = read_assignments(&f)?.concat();
+            print!("{}", coverage_report(&ranges, span));
+            return Ok(());
+        }
+        Some("graph") => {
+            let graph = OverlapGraph::new(&read_assignments(&f)?);
+            match modes.get(1).map(String::as_str) {
+                None | Some("json") => print!("{}", graph.to_json()),
+                Some("dot") => print!("{}", graph.to_dot()),
+                Some(_) => return Err("Usage: graph [json|dot]".to_string()),
+            }
+            return Ok(());
+        }
+        Some("query") => {
+            let assignments = read_assignments(&f)?;
+            let mut labels = Vec::new();
+            for (i, ranges) in assignments.iter().enumerate() {
+                for (j, range) in ranges.iter().enumerate() {
+                    labels.push(format!("{}#{} {}", i + 1, j + 1, range));
+                }
+            }
+            let index = IntervalIndex::new(&assignments.concat());
+            for query in std::io::stdin().lock().lines() {
+                let query = match query {
+                    Ok(x) => x,
+                    Err(e) => return Err(format!("Could not read query: {}", e)),
+                };
+                if query.trim().is_empty() {
+                    continue;
+                }
+                match answer_query(&index, &labels, &query) {
+                    Ok(answer) => println!("{}", answer),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+            return Ok(());
+        }
+        Some(mode) => return Err(format!("Unknown mode: {}", mode)),
+    }
+
     println!("Part One: {}", solve_part_one(&f)?);
     if let Err(e) = f.rewind() {
         return Err(format!("Could not rewind file: {}", e));
@@ -118,6 +295,14 @@ fn main() -> Result<(), String> {
     Ok(())
 }
 
+fn main() {
+    // Printed with Display rather than returned so multi-line range errors keep their layout
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
 #[test]
 fn test_input() -> Result<(), String> {
     let input = r#"2-4,6-8
@@ -135,3 +320,364 @@ fn test_input() -> Result<(), String> {
 
     Ok(())
 }
+
+#[test]
+fn test_roster() -> Result<(), String> {
+    let ranges = parse_ranges("2-8,3-7,4-5,9-9")?;
+    let report = LineReport::new(&ranges);
+    assert_eq!(report.containing, vec![(0, 1), (0, 2), (1, 2)]);
+    assert_eq!(report.overlapping, vec![(0, 1), (0, 2), (1, 2)]);
+    assert_eq!(report.cover, None);
+    assert_eq!(
+        report.to_string(),
+        "contains #1 > #2, #1 > #3, #2 > #3; overlaps #1 & #2, #1 & #3, #2 & #3; \
+         no range covers all"
+    );
+
+    let report = LineReport::new(&parse_ranges("4-6,1-9,5-5,4-6")?);
+    assert_eq!(
+        report.containing,
+        vec![(1, 0), (0, 2), (0, 3), (1, 2), (1, 3), (3, 2)]
+    );
+    assert_eq!(report.cover, Some(1));
+    assert_eq!(LineReport::new(&parse_ranges("3-4")?).cover, Some(0));
+    assert!(parse_ranges("1-2,").is_err());
+
+    let input = "1-2,3-4,5-6\n1-9,3-4,5-6\n1-3,3-4,7-8\n";
+    assert_eq!(solve_part_one(input.as_bytes())?, 1);
+    assert_eq!(solve_part_two(input.as_bytes())?, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_interval() -> Result<(), String> {
+    use std::collections::BTreeSet;
+
+    // Every interval within 0..=7 against every other, compared with plain sets of sections
+    let point = |x| Range::new(x, x).unwrap();
+    let sections = |r: &Range| {
+        (0..=9)
+            .filter(|x| r.contains(&point(*x)))
+            .collect::<BTreeSet<i64>>()
+    };
+    let union_of = |rs: &[Range]| rs.iter().flat_map(sections).collect::<BTreeSet<i64>>();
+    let ranges = (0..=7)
+        .flat_map(|b| (b..=7).map(move |e| Range::new(b, e).unwrap()))
+        .collect::<Vec<Range>>();
+    for a in &ranges {
+        let sa = sections(a);
+        assert_eq!(a.len(), sa.len() as u128);
+        for b in &ranges {
+            let sb = sections(b);
+            let common = sa.intersection(&sb).copied().collect::<BTreeSet<i64>>();
+            let both = sa.union(&sb).copied().collect::<BTreeSet<i64>>();
+            assert_eq!(a.contains(b), sb.is_subset(&sa), "{} contains {}", a, b);
+            assert_eq!(a.overlaps(b), !common.is_empty(), "{} overlaps {}", a, b);
+            assert_eq!(a.overlaps(b), b.overlaps(a));
+            assert_eq!(
+                a.intersection(b).map_or(BTreeSet::new(), |r| sections(&r)),
+                common
+            );
+            let contiguous = both.len() as i64 == both.last().unwrap() - both.first().unwrap() + 1;
+            assert_eq!(a.union(b).map(|r| sections(&r)), contiguous.then_some(both));
+            assert_eq!(
+                a.is_adjacent(b),
+                common.is_empty() && contiguous,
+                "{} adjacent to {}",
+                a,
+                b
+            );
+            let difference = a.difference(b);
+            assert!(difference.windows(2).all(|w| !w[0].overlaps(&w[1])));
+            assert_eq!(
+                union_of(&difference),
+                sa.difference(&sb).copied().collect::<BTreeSet<i64>>()
+            );
+        }
+    }
+
+    type Narrow = interval::Interval<u32>;
+    let top = Narrow::new(u32::MAX - 1, u32::MAX).unwrap();
+    let bottom = Narrow::new(0, 1).unwrap();
+    let all = Narrow::new(0, u32::MAX).unwrap();
+    assert_eq!(all.len(), 1 << 32);
+    assert_eq!(
+        all.difference(&top),
+        vec![Narrow::new(0, u32::MAX - 2).unwrap()]
+    );
+    assert_eq!(
+        all.difference(&bottom),
+        vec![Narrow::new(2, u32::MAX).unwrap()]
+    );
+    assert!(all.difference(&all).is_empty());
+    let all = Range::new(i64::MIN, i64::MAX).unwrap();
+    assert_eq!(all.len(), 1 << 64);
+    assert_eq!(Range::new(0, i64::MAX).unwrap().len(), 1 << 63);
+    assert!(Range::new(3, 2).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_coverage() -> Result<(), String> {
+    let input = "2-4,6-8\n3-5,9-9\n12-14,4-4\n";
+    let ranges = read_assignments(input.as_bytes())?.concat();
+    let covered = IntervalSet::new(&ranges);
+    assert_eq!(covered.intervals(), parse_ranges("2-9,12-14")?);
+    assert_eq!(covered.len(), 11);
+    let runs = coverage::multiplicity(&ranges);
+    let depths = runs.iter().map(|(run, depth)| (run.to_string(), *depth));
+    assert_eq!(
+        depths.collect::<Vec<(String, usize)>>(),
+        [
+            ("2 - 2", 1),
+            ("3 - 3", 2),
+            ("4 - 4", 3),
+            ("5 - 9", 1),
+            ("12 - 14", 1)
+        ]
+        .map(|(run, depth)| (run.to_string(), depth))
+    );
+    assert_eq!(
+        runs.into_iter()
+            .filter(|(_, depth)| *depth > 1)
+            .collect::<Vec<(Range, usize)>>(),
+        vec![
+            (Range::new(3, 3).unwrap(), 2),
+            (Range::new(4, 4).unwrap(), 3)
+        ]
+    );
+    assert_eq!(
+        covered.gaps(&Range::new(0, 20).unwrap()),
+        parse_ranges("0-1,10-11,15-20")?
+    );
+    assert!(covered.gaps(&Range::new(3, 8).unwrap()).is_empty());
+
+    assert_eq!(
+        coverage_report(&ranges, None),
+        "Covered: 11 sections in 2 - 9, 12 - 14\n\
+         Covered more than once: 2 sections\n\
+         \x20 3 - 3 x2\n\
+         \x20 4 - 4 x3\n\
+         Gaps in 2 - 14: 10 - 11\n"
+    );
+
+    let top = [
+        interval::Interval::<u32>::new(u32::MAX - 2, u32::MAX).unwrap(),
+        interval::Interval::<u32>::new(u32::MAX, u32::MAX).unwrap(),
+    ];
+    assert_eq!(
+        coverage::multiplicity(&top),
+        vec![
+            (
+                interval::Interval::<u32>::new(u32::MAX - 2, u32::MAX - 1).unwrap(),
+                1
+            ),
+            (
+                interval::Interval::<u32>::new(u32::MAX, u32::MAX).unwrap(),
+                2
+            )
+        ]
+    );
+    // Open ends reach the limits of i64, where section counts no longer fit in a u64
+    let ranges = parse_ranges("-0,-0,1-,1-,1-")?;
+    assert_eq!(
+        coverage_report(&ranges, None),
+        "Covered: 18446744073709551616 sections in -9223372036854775808 - 9223372036854775807\n\
+         Covered more than once: 18446744073709551616 sections\n\
+         \x20 -9223372036854775808 - 0 x2\n\
+         \x20 1 - 9223372036854775807 x3\n\
+         Gaps in -9223372036854775808 - 9223372036854775807: none\n"
+    );
+    let ranges = parse_ranges("-0,5-,7-9")?;
+    assert_eq!(
+        IntervalSet::new(&ranges).len(),
+        (1 << 63) + 1 + (1 << 63) - 5
+    );
+    assert_eq!(
+        coverage_report(&ranges, Some(parse_range("-2-8")?)),
+        "Covered: 18446744073709551612 sections in -9223372036854775808 - 0, \
+         5 - 9223372036854775807\n\
+         Covered more than once: 3 sections\n\
+         \x20 7 - 9 x2\n\
+         Gaps in -2 - 8: 1 - 4\n"
+    );
+    assert!(coverage::multiplicity::<i64>(&[]).is_empty());
+
+    Ok(())
+}
+
+/// Deterministic xorshift generator for the randomized tests
+#[cfg(test)]
+struct XorShift(u64);
+
+#[cfg(test)]
+impl XorShift {
+    fn next(&mut self, bound: u64) -> i64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound) as i64
+    }
+}
+
+#[test]
+fn test_graph() -> Result<(), String> {
+    let input = "1-3,2-5\n4-6,8-9\n3-4,9-12\n";
+    let graph = OverlapGraph::new(&read_assignments(input.as_bytes())?);
+    assert_eq!(
+        graph.edges,
+        vec![(0, 1), (0, 4), (1, 2), (1, 4), (2, 4), (3, 5)]
+    );
+    assert_eq!(graph.components, vec![0, 0, 0, 1, 0, 1]);
+    assert_eq!(graph.component_count(), 2);
+    // 1-3, 2-5 and 3-4 all cover section 3, as 2-5, 4-6 and 3-4 do section 4
+    assert_eq!(graph.clique, vec![0, 1, 4]);
+    assert_eq!(graph.color_count(), 3);
+    for (a, b) in &graph.edges {
+        assert_ne!(graph.colors[*a], graph.colors[*b]);
+    }
+
+    // Against a pairwise scan over random ranges
+    let mut rng = XorShift(0x2545_f491);
+    let assignments = (0..40)
+        .map(|_| {
+            (0..3)
+                .map(|_| {
+                    let begin = rng.next(60);
+                    Range::new(begin, begin + rng.next(8)).unwrap()
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<Range>>>();
+    let graph = OverlapGraph::new(&assignments);
+    let ranges = assignments.concat();
+    let mut edges = Vec::new();
+    for i in 0..ranges.len() {
+        for j in i + 1..ranges.len() {
+            if ranges[i].overlaps(&ranges[j]) {
+                edges.push((i, j));
+            }
+        }
+    }
+    assert_eq!(graph.edges, edges);
+    assert!(edges
+        .iter()
+        .all(|(a, b)| graph.colors[*a] != graph.colors[*b]));
+    assert!(edges
+        .iter()
+        .all(|(a, b)| graph.components[*a] == graph.components[*b]));
+    for (i, a) in graph.clique.iter().enumerate() {
+        assert!(graph.clique[i + 1..]
+            .iter()
+            .all(|b| edges.contains(&(*a, *b))));
+    }
+    let deepest = coverage::multiplicity(&ranges)
+        .iter()
+        .map(|(_, depth)| *depth)
+        .max();
+    assert_eq!(Some(graph.clique.len()), deepest);
+    assert_eq!(graph.color_count(), graph.clique.len());
+
+    let graph = OverlapGraph::new(&[vec![Range::new(1, 2).unwrap()]]);
+    assert_eq!(
+        graph.to_json(),
+        "{\n  \"nodes\": [\n    {\"id\": 0, \"line\": 1, \"position\": 1, \"begin\": 1, \
+         \"end\": 2, \"component\": 0, \"color\": 0}\n  ],\n  \"edges\": [],\n  \
+         \"components\": 1,\n  \"max_clique\": [0],\n  \"colors\": 1\n}\n"
+    );
+    assert_eq!(
+        graph.to_dot(),
+        "graph overlaps {\n  0 [label=\"1#1: 1 - 2\", style=filled, fillcolor=\"/set312/1\"];\n}\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_index() -> Result<(), String> {
+    let ranges = parse_ranges("2-4,6-8,3-5,9-9,1-20,5-5")?;
+    let index = IntervalIndex::new(&ranges);
+    assert_eq!(index.stab(5), vec![2, 4, 5]);
+    assert_eq!(index.stab(21), Vec::<usize>::new());
+    assert_eq!(index.overlapping(&Range::new(8, 9).unwrap()), vec![1, 3, 4]);
+
+    let labels = ["a", "b", "c", "d", "e", "f"].map(String::from);
+    assert_eq!(answer_query(&index, &labels, " 4 ")?, "4: 3 [a, c, e]");
+    assert_eq!(answer_query(&index, &labels, "9-30")?, "9-30: 2 [d, e]");
+    assert_eq!(answer_query(&index, &labels, "9-")?, "9-: 2 [d, e]");
+    assert_eq!(answer_query(&index, &labels, "-1")?, "-1: 1 [e]");
+    assert!(answer_query(&index, &labels, "4-x").is_err());
+    assert!(answer_query(&index, &labels, "x").is_err());
+    assert!(IntervalIndex::<i64>::new(&[]).stab(1).is_empty());
+
+    // Against a linear scan over random ranges
+    let mut rng = XorShift(0x9e37_79b9);
+    let ranges = (0..500)
+        .map(|_| {
+            let begin = rng.next(1000);
+            Range::new(begin, begin + rng.next(50)).unwrap()
+        })
+        .collect::<Vec<Range>>();
+    let index = IntervalIndex::new(&ranges);
+    for _ in 0..200 {
+        let begin = rng.next(1100);
+        let query = Range::new(begin, begin + rng.next(20)).unwrap();
+        let expected = (0..ranges.len())
+            .filter(|i| ranges[*i].overlaps(&query))
+            .collect::<Vec<usize>>();
+        assert_eq!(index.overlapping(&query), expected);
+        let expected = (0..ranges.len())
+            .filter(|i| ranges[*i].contains(&Range::new(begin, begin).unwrap()))
+            .collect::<Vec<usize>>();
+        assert_eq!(index.stab(begin), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_range_grammar() -> Result<(), String> {
+    use range::RangeErrorKind;
+
+    let range = |begin, end| Range::new(begin, end).unwrap();
+    assert_eq!(parse_range("2-4")?, range(2, 4));
+    assert_eq!(parse_range("-5--2")?, range(-5, -2));
+    assert_eq!(parse_range("-5-3")?, range(-5, 3));
+    assert_eq!(parse_range("3-")?, range(3, i64::MAX));
+    assert_eq!(parse_range("-5-")?, range(-5, i64::MAX));
+    assert_eq!(parse_range("-7")?, range(i64::MIN, 7));
+    assert_eq!(parse_range("--7")?, range(i64::MIN, -7));
+    assert_eq!(parse_range("6")?, range(6, 6));
+
+    let kind = |text| parse_ranges(text).map_err(|e| (e.kind, e.column, e.span));
+    assert_eq!(kind("1-2,-"), Err((RangeErrorKind::MissingBound, 5, 1)));
+    assert_eq!(kind("1-2,"), Err((RangeErrorKind::MissingBound, 5, 0)));
+    assert_eq!(kind("1-2,3-x4"), Err((RangeErrorKind::InvalidNumber, 7, 2)));
+    assert_eq!(kind("1-2,3--"), Err((RangeErrorKind::InvalidNumber, 7, 1)));
+    assert_eq!(kind("5-3"), Err((RangeErrorKind::Reversed, 1, 3)));
+    assert_eq!(
+        kind("1-99999999999999999999"),
+        Err((RangeErrorKind::Overflow, 3, 20))
+    );
+
+    // The error for the end of a range points at the end, not the beginning
+    assert_eq!(
+        parse_ranges("2-4,6-8x").unwrap_err().to_string(),
+        "invalid section number at column 7\n  2-4,6-8x\n        ^^"
+    );
+    assert_eq!(
+        read_assignments("1-2\n3-4,a-5\n".as_bytes()),
+        Err(
+            "Invalid range on line 2: invalid section number at column 5\n  3-4,a-5\n      ^"
+                .to_string()
+        )
+    );
+
+    let input = "-5--2,-3\n3-,-7\n6,1-5\n";
+    assert_eq!(solve_part_one(input.as_bytes())?, 1);
+    assert_eq!(solve_part_two(input.as_bytes())?, 2);
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use core::fmt::Formatter;
+use std::fmt::Display;
+use std::num::IntErrorKind;
+
+use crate::interval::Interval;
+
+/// Sections are signed, and an open end reaches the lowest or highest section
+pub type Range = Interval<i64>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeErrorKind {
+    /// Neither bound given, as in `-` or an empty range
+    MissingBound,
+    InvalidNumber,
+    Overflow,
+    /// The first section is after the last
+    Reversed,
+}
+
+impl Display for RangeErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            RangeErrorKind::MissingBound => write!(f, "range has no bounds"),
+            RangeErrorKind::InvalidNumber => write!(f, "invalid section number"),
+            RangeErrorKind::Overflow => write!(f, "section number out of range"),
+            RangeErrorKind::Reversed => write!(f, "range ends before it begins"),
+        }
+    }
+}
+
+/// A range that could not be parsed. `column` is 1-based and, with `span`, marks the failing
+/// token within `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeError {
+    pub text: String,
+    pub column: usize,
+    pub span: usize,
+    pub kind: RangeErrorKind,
+}
+
+impl Display for RangeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        writeln!(f, "{} at column {}", self.kind, self.column)?;
+        writeln!(f, "  {}", self.text)?;
+        write!(
+            f,
+            "  {}{}",
+            " ".repeat(self.column - 1),
+            "^".repeat(self.span.max(1))
+        )
+    }
+}
+
+impl From<RangeError> for String {
+    fn from(e: RangeError) -> String {
+        e.to_string()
+    }
+}
+
+/// Parses a range such as `2-4`, `-5--2`, `3-` (from 3 up), `-7` (up to 7) or `6` (just 6).
+///
+/// The separator is the first `-` after the start that doesn't follow another `-`; failing that,
+/// a leading `-` is the separator rather than a sign.
+pub fn parse_range(text: &str) -> Result<Range, RangeError> {
+    let error = |start: usize, token: &str, kind| RangeError {
+        text: text.to_string(),
+        column: text[..start].chars().count() + 1,
+        span: token.chars().count(),
+        kind,
+    };
+    let separator = text
+        .char_indices()
+        .skip(1)
+        .find(|(i, c)| *c == '-' && !text[..*i].ends_with('-'))
+        .map(|(i, _)| i)
+        .or_else(|| text.starts_with('-').then_some(0));
+
+    let bound = |start: usize, token: &str| match token.parse::<i64>() {
+        Ok(x) => Ok(Some(x)),
+        Err(_) if token.is_empty() => Ok(None),
+        Err(e) => match e.kind() {
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+                Err(error(start, token, RangeErrorKind::Overflow))
+            }
+            _ => Err(error(start, token, RangeErrorKind::InvalidNumber)),
+        },
+    };
+    let (begin, end) = match separator {
+        Some(i) => (bound(0, &text[..i])?, bound(i + 1, &text[i + 1..])?),
+        None => {
+            let section = bound(0, text)?;
+            (section, section)
+        }
+    };
+    if begin.is_none() && end.is_none() {
+        return Err(error(0, text, RangeErrorKind::MissingBound));
+    }
+    let begin = begin.unwrap_or(i64::MIN);
+    let end = end.unwrap_or(i64::MAX);
+    Range::new(begin, end).ok_or_else(|| error(0, text, RangeErrorKind::Reversed))
+}
+
+/// Parses every comma-separated range of a line
+pub fn parse_ranges(line: &str) -> Result<Vec<Range>, RangeError> {
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    for part in line.split(',') {
+        match parse_range(part) {
+            Ok(range) => ranges.push(range),
+            Err(e) => {
+                return Err(RangeError {
+                    text: line.to_string(),
+                    column: e.column + line[..offset].chars().count(),
+                    ..e
+                })
+            }
+        }
+        offset += part.len() + 1;
+    }
+    Ok(ranges)
+}